@@ -0,0 +1,258 @@
+use std::io;
+use std::path::Path;
+
+use crate::diff::*;
+use crate::export::*;
+use crate::import::*;
+use crate::json::*;
+use crate::plan::*;
+use crate::monitor::*;
+use crate::profile::*;
+use crate::xrandr::*;
+use crate::{Align, Dir};
+
+pub const USAGE: &str = "\
+usage: monitor_tui [-d] [--import <file>] [--json] [--record <dir>]
+       monitor_tui --replay <dir>
+       monitor_tui --script <file>
+       monitor_tui [-d] list [--json]
+       monitor_tui [-d] apply <profile>
+       monitor_tui [-d] export [--format script|xorg|command]
+       monitor_tui [-d] set <output> [--mode WxH] [--rate R] [--pos XxY] [--scale S] [--primary] [--off]
+       monitor_tui [-d] place <output> --right-of|--left-of|--above|--below <output> [--align top|bottom|left|right|center]
+       monitor_tui [-d] --save-profile <name>
+       monitor_tui [-d] --watch
+
+  -d                debug mode, using built in mock monitors
+  --input <file>    load monitors from saved `xrandr --query` output, implies --dry-run
+  --dry-run         print the xrandr command instead of running it";
+
+// options `set` understands, everything else is rejected rather than silently ignored
+const SET_OPTIONS: [&str; 6] = ["--mode", "--rate", "--pos", "--scale", "--primary", "--off"];
+// the ones followed by a value, which can start with - itself, e.g. --pos -1920x0
+const SET_VALUE_OPTIONS: [&str; 4] = ["--mode", "--rate", "--pos", "--scale"];
+
+fn invalid(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, format!("{}\n{}", message, USAGE))
+}
+
+// anything past what a command takes is a mistake, not something to skip over
+fn no_more_args(args: &[String], taken: usize) -> io::Result<()> {
+    match args.get(taken) {
+        Some(arg) => Err(invalid(format!("unexpected argument '{}' for {}", arg, args[0]))),
+        None => Ok(()),
+    }
+}
+
+// one line per monitor, e.g. HDMI-1 2560x1440+0+0 60hz scale 1.00 primary (DELL U2719D)
+pub fn list_monitors(monitors: &Monitors) -> String {
+    let mut list = String::new();
+    for monitor in monitors {
+        list.push_str(&monitor.name);
+        if monitor.is_enabled {
+            list.push_str(&format!(
+                " {}x{}+{}+{} {}hz scale {:.2}",
+                monitor.resolution.0,
+                monitor.resolution.1,
+                monitor.position.0,
+                monitor.position.1,
+                monitor.framerate,
+                monitor.resolution.0 as f32 / monitor.displayed_resolution.0 as f32
+            ));
+            if monitor.is_primary {
+                list.push_str(" primary");
+            }
+        } else {
+            list.push_str(" disabled");
+        }
+        if let Some(edid) = &monitor.edid {
+            list.push_str(&format!(" ({})", edid.model_name()));
+        }
+        list.push('\n');
+    }
+    list
+}
+
+pub fn export_monitors(monitors: &Monitors, format: &str) -> io::Result<String> {
+    match format {
+        "script" => Ok(to_shell_script(monitors)),
+        "xorg" => Ok(to_xorg_conf(monitors)),
+        "command" => Ok(format!("xrandr {}\n", convert_monitors_to_args(monitors, false).join(" "))),
+        _ => Err(invalid(format!("unknown export format '{}'", format))),
+    }
+}
+
+// change a single output, moving its neighbours along with it like editing it in the tui would
+pub fn set_output(monitors: &mut Monitors, name: &str, options: &[String]) -> io::Result<()> {
+    let idx = monitors.iter()
+        .position(|m| m.name == name)
+        .ok_or_else(|| invalid(format!("unknown output '{}'", name)))?;
+    let mut rest = options.iter();
+    while let Some(option) = rest.next() {
+        if !SET_OPTIONS.contains(&option.as_str()) {
+            return Err(invalid(format!("unknown option '{}' for set", option)));
+        }
+        if SET_VALUE_OPTIONS.contains(&option.as_str()) && rest.next().is_none() {
+            return Err(invalid(format!("missing value for {}", option)));
+        }
+    }
+
+    let mut args = vec!["--output".to_string(), name.to_string()];
+    args.extend_from_slice(options);
+    let mut config = parse_xrandr_args(&args)?.remove(0);
+
+    if config.off {
+        if !disable_monitor(monitors, idx) {
+            return Err(invalid(format!("can't turn off {}, it's the only enabled output", name)));
+        }
+        return Ok(());
+    }
+    if !monitors[idx].is_enabled {
+        let anchor_idx = monitors.iter().position(|m| m.is_enabled).unwrap_or(idx);
+        enable_monitor(monitors, idx, anchor_idx);
+    }
+
+    // work the new size out on a copy, so anything after the output can be shifted by the difference
+    let position = config.position.take();
+    let mut updated = monitors.clone();
+    apply_output_configs(&mut updated, &[config])?;
+    let old_res = monitors[idx].displayed_resolution;
+    let new_res = updated[idx].displayed_resolution;
+    shift_res(monitors, idx, (new_res.0 - old_res.0, new_res.1 - old_res.1));
+
+    for (monitor, new) in monitors.iter_mut().zip(updated.iter()) {
+        monitor.is_primary = new.is_primary;
+    }
+    let monitor = &mut monitors[idx];
+    monitor.resolution = updated[idx].resolution;
+    monitor.displayed_resolution = updated[idx].displayed_resolution;
+    monitor.scale = updated[idx].scale;
+    monitor.framerate = updated[idx].framerate;
+    if let Some(position) = position {
+        monitor.position = position;
+    }
+
+    normalise_positions(monitors);
+    reset_proximity(monitors);
+    Ok(())
+}
+
+// put an output next to another one, e.g. place DP-1 --right-of HDMI-1 --align bottom
+pub fn place_output(monitors: &mut Monitors, name: &str, options: &[String]) -> io::Result<()> {
+    let find = |monitors: &Monitors, name: &str| monitors.iter()
+        .position(|m| m.name == name)
+        .ok_or_else(|| invalid(format!("unknown output '{}'", name)));
+    let idx = find(monitors, name)?;
+
+    let mut relative_to: Option<(Dir, usize)> = None;
+    let mut align: Option<&str> = None;
+    let mut options = options.iter();
+    while let Some(option) = options.next() {
+        let value = options.next().ok_or_else(|| invalid(format!("missing value for {}", option)))?;
+        let direction = match option.as_str() {
+            "--right-of" => Dir::Right,
+            "--left-of" => Dir::Left,
+            "--above" => Dir::Up,
+            "--below" => Dir::Down,
+            "--align" => {
+                align = Some(value);
+                continue;
+            }
+            _ => return Err(invalid(format!("unknown option '{}' for place", option))),
+        };
+        relative_to = Some((direction, find(monitors, value)?));
+    }
+
+    let Some((direction, other_idx)) = relative_to else {
+        return Err(invalid("place needs one of --right-of, --left-of, --above or --below".to_string()));
+    };
+    if other_idx == idx {
+        return Err(invalid(format!("can't place {} relative to itself", name)));
+    }
+    if !monitors[other_idx].is_enabled {
+        return Err(invalid(format!("can't place relative to {}, it's turned off", monitors[other_idx].name)));
+    }
+
+    // monitors side by side line up vertically, stacked ones horizontally
+    // - top or left edges by default, the same as xrandr
+    let horizontal = matches!(direction, Dir::Left | Dir::Right);
+    let align = match (align.unwrap_or(if horizontal { "top" } else { "left" }), horizontal) {
+        ("top", true) | ("left", false) => Align::Start,
+        ("bottom", true) | ("right", false) => Align::End,
+        ("center", _) => Align::Center,
+        (value, _) => return Err(invalid(format!("can't align '{}' when placing {}", value, if horizontal { "side by side" } else { "above or below" }))),
+    };
+
+    if !monitors[idx].is_enabled {
+        enable_monitor(monitors, idx, other_idx);
+    }
+    place_monitor(monitors, idx, other_idx, direction, align);
+    Ok(())
+}
+
+// send the outputs that changed to the backend, turning a failed xrandr into an error
+// - returns what xrandr printed, which is the command itself on a dry run
+pub fn apply_monitors(backend: &mut dyn Backend, before: &Monitors, monitors: &Monitors) -> io::Result<String> {
+    if diff_args(before, monitors).is_empty() {
+        return Ok("Nothing to apply, the layout hasn't changed\n".to_string());
+    }
+    let result = apply_planned(backend, before, monitors);
+    if !result.success {
+        return Err(io::Error::other(format!("{} failed ({}): {}", result.command.lines().last().unwrap_or_default(), result.status, result.stderr.trim())));
+    }
+    Ok(result.stdout)
+}
+
+pub fn apply_profile(backend: &mut dyn Backend, dir: &Path, name: &str) -> io::Result<String> {
+    let profile = Profile::load(dir, name)
+        .map_err(|err| io::Error::new(err.kind(), format!("can't load profile {} from {}: {}", name, dir.display(), err)))?;
+    let before = backend.query()?;
+    let mut monitors = before.clone();
+    profile.apply_to(&mut monitors)?;
+    apply_monitors(backend, &before, &monitors)
+}
+
+// run a subcommand without the tui, returning whatever it prints
+pub fn run_command(backend: &mut dyn Backend, args: &[String]) -> io::Result<String> {
+    let Some(command) = args.first() else { return Err(invalid("missing command".to_string())) };
+    match command.as_str() {
+        "list" => match args.get(1).map(|a| a.as_str()) {
+            Some("--json") => {
+                no_more_args(args, 2)?;
+                Ok(monitors_to_json(&backend.query()?))
+            }
+            Some(other) => Err(invalid(format!("unknown option '{}' for list", other))),
+            None => Ok(list_monitors(&backend.query()?)),
+        },
+        "apply" => {
+            let name = args.get(1).ok_or_else(|| invalid("missing profile name for apply".to_string()))?;
+            no_more_args(args, 2)?;
+            let output = apply_profile(backend, &profiles_dir(), name)?;
+            Ok(format!("{}Applied profile {}\n", output, name))
+        }
+        "export" => {
+            let format = match args.get(1).map(|a| a.as_str()) {
+                Some("--format") => args.get(2).ok_or_else(|| invalid("missing value for --format".to_string()))?.as_str(),
+                Some(other) => return Err(invalid(format!("unknown option '{}' for export", other))),
+                None => "script",
+            };
+            no_more_args(args, if args.len() > 1 { 3 } else { 1 })?;
+            export_monitors(&backend.query()?, format)
+        }
+        "set" => {
+            let name = args.get(1).ok_or_else(|| invalid("missing output name for set".to_string()))?;
+            let before = backend.query()?;
+            let mut monitors = before.clone();
+            set_output(&mut monitors, name, &args[2..])?;
+            apply_monitors(backend, &before, &monitors)
+        }
+        "place" => {
+            let name = args.get(1).ok_or_else(|| invalid("missing output name for place".to_string()))?;
+            let before = backend.query()?;
+            let mut monitors = before.clone();
+            place_output(&mut monitors, name, &args[2..])?;
+            apply_monitors(backend, &before, &monitors)
+        }
+        _ => Err(invalid(format!("unknown command '{}'", command))),
+    }
+}
@@ -35,7 +35,8 @@ DP-2 connected 1920x1080+4480+0 (normal left inverted right x axis y axis) 521mm
    1280x720      60.00    59.94    50.00
    1024x768      75.03    60.00
    800x600       75.00    60.32
-   640x480       75.00    60.00    59.94"#;
+   640x480       75.00    60.00    59.94
+HDMI-2 disconnected (normal left inverted right x axis y axis)"#;
 
 pub const XRANDR_OUTPUT_3_DIFF: &str = r#"Screen 0: minimum 320 x 200, current 5760 x 1440, maximum 16384 x 16384
 HDMI-1 connected primary 2560x1440+0+0 (normal left inverted right x axis y axis) 597mm x 336mm
@@ -122,4 +123,43 @@ DP-3 connected 1920x1080+6400+0 (normal left inverted right x axis y axis) 521mm
    1024x768      75.03    60.00
    800x600       75.00    60.32
    640x480       75.00    60.00    59.94"#;
+
+    // Trimmed `xrandr --verbose` output with the EDID of each mock monitor
+    pub const XRANDR_VERBOSE_EDID: &str = r#"Screen 0: minimum 320 x 200, current 5760 x 1440, maximum 16384 x 16384
+HDMI-1 connected primary 2560x1440+0+0 (0x46) normal (normal left inverted right x axis y axis) 597mm x 336mm
+	Identifier: 0x42
+	EDID: 
+		00ffffffffffff0010acc4a03532334c
+		0c1d0104b53c22783a00000000000000
+		00000000000000000000000000000000
+		000000000000565e0000000000000000
+		0000000000000000000000fd00384c1e
+		5311000a202020202020000000fc0044
+		454c4c205532373139440a20000000ff
+		00374d54303138364b3041424c0a00bf
+	BorderDimensions: 4 
+DP-1 connected 1920x1080+2560+0 (0x4a) normal (normal left inverted right x axis y axis) 521mm x 293mm
+	Identifier: 0x43
+	EDID: 
+		00ffffffffffff001e6d7f5bf2e00100
+		1e1e0104b5341d783a00000000000000
+		00000000000000000000000000000000
+		000000000000565e0000000000000000
+		0000000000000000000000fd00384c1e
+		5311000a202020202020000000fc004c
+		47204648440a202020202020000000ff
+		003030354e54414231433132330a003c
+	BorderDimensions: 4 
+DP-2 connected 1920x1080+4480+0 (0x4a) normal (normal left inverted right x axis y axis) 521mm x 293mm
+	Identifier: 0x44
+	EDID: 
+		00ffffffffffff001e6d7f5ba8e20100
+		1f1e0104b5341d783a00000000000000
+		00000000000000000000000000000000
+		000000000000565e0000000000000000
+		0000000000000000000000fd00384c1e
+		5311000a202020202020000000fc004c
+		47204648440a202020202020000000ff
+		003030354e54414231433435360a007a
+	BorderDimensions: 4 "#;
 }
@@ -0,0 +1,68 @@
+use crate::monitor::*;
+use crate::xrandr::output_args;
+
+// an output that's set up differently in two layouts
+#[derive(Debug, Clone, PartialEq)]
+pub struct OutputDiff {
+    pub name: String,
+    pub before: String,
+    pub after: String,
+}
+
+// what xrandr is told about an output, nothing if it's off
+fn output_state(monitor: Option<&Monitor>) -> Option<Vec<String>> {
+    monitor.filter(|m| m.is_enabled).map(output_args)
+}
+
+// how an output is set up, as shown either side of the before/after table
+pub fn describe_output(monitor: Option<&Monitor>) -> String {
+    match monitor {
+        None => "not connected".to_string(),
+        Some(monitor) if !monitor.is_enabled => "off".to_string(),
+        Some(monitor) => format!(
+            "{}x{} {}hz +{}+{} scale {:.2}{}",
+            monitor.resolution.0,
+            monitor.resolution.1,
+            monitor.framerate,
+            monitor.position.0,
+            monitor.position.1,
+            monitor.scale,
+            if monitor.is_primary { " primary" } else { "" }
+        ),
+    }
+}
+
+// the outputs that changed, in the order they appear in the new layout
+pub fn diff_layouts(before: &Monitors, after: &Monitors) -> Vec<OutputDiff> {
+    after.iter()
+        .filter_map(|monitor| {
+            let old = before.iter().find(|m| m.name == monitor.name);
+            if output_state(old) == output_state(Some(monitor)) {
+                return None;
+            }
+            Some(OutputDiff { name: monitor.name.clone(), before: describe_output(old), after: describe_output(Some(monitor)) })
+        })
+        .collect()
+}
+
+// xrandr arguments for just the outputs that changed, so the rest of the screens are left alone
+pub fn diff_args(before: &Monitors, after: &Monitors) -> Vec<String> {
+    let changed: Vec<&Monitor> = after.iter()
+        .filter(|monitor| output_state(before.iter().find(|m| m.name == monitor.name)) != output_state(Some(monitor)))
+        .collect();
+
+    let mut args: Vec<String> = Vec::new();
+    for monitor in changed.iter().filter(|m| m.is_enabled) {
+        args.extend(output_args(monitor));
+    }
+    for monitor in changed.iter().filter(|m| !m.is_enabled) {
+        args.extend(["--output".to_string(), monitor.name.clone(), "--off".to_string()]);
+    }
+
+    // dropping --primary from an output doesn't unset it, unless another one takes over
+    let had_primary = before.iter().any(|m| m.is_enabled && m.is_primary);
+    if had_primary && !after.iter().any(|m| m.is_enabled && m.is_primary) {
+        args.push("--noprimary".to_string());
+    }
+    args
+}
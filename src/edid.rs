@@ -0,0 +1,166 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+// identity of a panel decoded from its EDID, stays the same whichever port it's plugged into
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Edid {
+    pub manufacturer: String,           // three letter PNP id, e.g. DEL
+    pub product_code: u16,
+    pub serial: u32,
+    pub serial_string: Option<String>,  // serial number descriptor, more reliable than the number
+    pub model: Option<String>,          // monitor name descriptor
+    pub year: u16,
+}
+
+const EDID_HEADER: [u8; 8] = [0x00, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x00];
+
+impl Edid {
+    // stable identifier used to recognise the same panel across ports
+    pub fn fingerprint(&self) -> String {
+        let serial = self.serial_string.clone().unwrap_or_else(|| self.serial.to_string());
+        format!("{}-{:04x}-{}", self.manufacturer, self.product_code, serial)
+    }
+
+    pub fn model_name(&self) -> String {
+        self.model.clone().unwrap_or_else(|| format!("{} {:04x}", self.manufacturer, self.product_code))
+    }
+}
+
+// decode the base EDID block
+pub fn parse_edid(bytes: &[u8]) -> Option<Edid> {
+    if bytes.len() < 128 || bytes[0..8] != EDID_HEADER {
+        return None;
+    }
+
+    // manufacturer id is three 5 bit letters, big endian
+    let id = u16::from_be_bytes([bytes[8], bytes[9]]);
+    let manufacturer: String = [10, 5, 0]
+        .iter()
+        .map(|shift| (b'A' - 1 + ((id >> shift) & 0x1f) as u8) as char)
+        .collect();
+
+    let mut edid = Edid {
+        manufacturer,
+        product_code: u16::from_le_bytes([bytes[10], bytes[11]]),
+        serial: u32::from_le_bytes([bytes[12], bytes[13], bytes[14], bytes[15]]),
+        serial_string: None,
+        model: None,
+        year: 1990 + bytes[17] as u16,
+    };
+
+    // display descriptors live in the four 18 byte blocks after the timings
+    for offset in [54, 72, 90, 108] {
+        let descriptor = &bytes[offset..offset + 18];
+        if descriptor[0..3] != [0, 0, 0] {
+            continue;
+        }
+        let text: String = descriptor[5..18]
+            .iter()
+            .take_while(|b| **b != 0x0a)
+            .map(|b| *b as char)
+            .collect::<String>()
+            .trim()
+            .to_string();
+        match descriptor[3] {
+            0xfc if !text.is_empty() => edid.model = Some(text),
+            0xff if !text.is_empty() => edid.serial_string = Some(text),
+            _ => {}
+        }
+    }
+
+    Some(edid)
+}
+
+fn parse_hex(hex: &str) -> Vec<u8> {
+    hex.as_bytes()
+        .chunks(2)
+        .filter_map(|pair| std::str::from_utf8(pair).ok().and_then(|s| u8::from_str_radix(s, 16).ok()))
+        .collect()
+}
+
+// collect the EDID of every output from `xrandr --verbose`
+pub fn parse_verbose_edids(verbose: &str) -> HashMap<String, Edid> {
+    let mut edids: HashMap<String, Edid> = HashMap::new();
+    let mut current_output: Option<String> = None;
+    let mut hex = String::new();
+    let mut in_edid = false;
+
+    for line in verbose.lines() {
+        if in_edid {
+            let trimmed = line.trim();
+            if !trimmed.is_empty() && trimmed.chars().all(|c| c.is_ascii_hexdigit()) {
+                hex.push_str(trimmed);
+                continue;
+            }
+            in_edid = false;
+            if let (Some(name), Some(edid)) = (current_output.as_ref(), parse_edid(&parse_hex(&hex))) {
+                edids.insert(name.clone(), edid);
+            }
+            hex.clear();
+        }
+
+        if !line.starts_with(char::is_whitespace) && (line.contains(" connected") || line.contains(" disconnected")) {
+            current_output = line.split_whitespace().next().map(|name| name.to_string());
+        } else if line.trim() == "EDID:" {
+            in_edid = true;
+        }
+    }
+    if in_edid {
+        if let (Some(name), Some(edid)) = (current_output.as_ref(), parse_edid(&parse_hex(&hex))) {
+            edids.insert(name.clone(), edid);
+        }
+    }
+
+    edids
+}
+
+// sysfs names connectors differently to xrandr (card0-HDMI-A-1 vs HDMI-1 or HDMI-A-1), so
+// compare on a normalised name
+pub fn normalise_connector(name: &str) -> String {
+    let name = match name.split_once('-') {
+        Some((card, rest)) if card.starts_with("card") => rest,
+        _ => name,
+    };
+    name.replace("-A-", "-").replace("-B-", "-")
+}
+
+// collect the EDID of every connector from /sys/class/drm, keyed by normalised connector name
+pub fn read_sysfs_edids(drm_root: &Path) -> HashMap<String, Edid> {
+    let mut edids: HashMap<String, Edid> = HashMap::new();
+    let Ok(entries) = fs::read_dir(drm_root) else { return edids };
+
+    for entry in entries.flatten() {
+        let name = entry.file_name().to_string_lossy().to_string();
+        if !name.starts_with("card") || !name.contains('-') {
+            continue;
+        }
+        if let Some(edid) = fs::read(entry.path().join("edid")).ok().and_then(|bytes| parse_edid(&bytes)) {
+            edids.insert(normalise_connector(&name), edid);
+        }
+    }
+
+    edids
+}
+
+// query the EDID of every output, falling back to sysfs for any xrandr doesn't report
+pub fn query_edids(output_names: &[String]) -> HashMap<String, Edid> {
+    let mut edids = match Command::new("xrandr").arg("--verbose").output() {
+        Ok(output) => parse_verbose_edids(&String::from_utf8_lossy(&output.stdout)),
+        Err(_) => HashMap::new(),
+    };
+
+    if output_names.iter().any(|name| !edids.contains_key(name)) {
+        let sysfs_edids = read_sysfs_edids(Path::new("/sys/class/drm"));
+        for name in output_names {
+            if !edids.contains_key(name) {
+                if let Some(edid) = sysfs_edids.get(&normalise_connector(name)) {
+                    edids.insert(name.clone(), edid.clone());
+                }
+            }
+        }
+    }
+
+    edids
+}
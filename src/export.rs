@@ -0,0 +1,64 @@
+use std::fs;
+use std::io;
+use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
+
+use crate::monitor::*;
+use crate::xrandr::*;
+
+// default file names used when exporting from the tui
+pub const SCRIPT_FILE: &str = "monitor-layout.sh";
+pub const XORG_CONF_FILE: &str = "10-monitor.conf";
+
+// runnable shell script applying the layout, one output per line
+// - disabled outputs are turned off after the enabled ones, the same as when applying
+pub fn to_shell_script(monitors: &Monitors) -> String {
+    let mut script = String::from("#!/bin/sh\n# Monitor layout generated by monitor-tui\nxrandr");
+    for monitor in monitors.iter().filter(|m| m.is_enabled) {
+        script.push_str(" \\\n    ");
+        script.push_str(&output_args(monitor).join(" "));
+    }
+    for monitor in monitors.iter().filter(|m| !m.is_enabled) {
+        script.push_str(" \\\n    ");
+        script.push_str(&off_args(monitor).join(" "));
+    }
+    script.push('\n');
+    script
+}
+
+// xorg.conf.d snippet so the layout also applies to the login screen
+// - xorg has no equivalent to --rate or --scale, so only the mode and placement are kept
+pub fn to_xorg_conf(monitors: &Monitors) -> String {
+    let mut conf = String::from("# Monitor layout generated by monitor-tui\n");
+    for monitor in monitors.iter() {
+        conf.push_str("\nSection \"Monitor\"\n");
+        conf.push_str(&format!("    Identifier \"{}\"\n", monitor.name));
+        if !monitor.is_enabled {
+            conf.push_str("    Option \"Enable\" \"false\"\n");
+            conf.push_str("EndSection\n");
+            continue;
+        }
+        if monitor.is_primary {
+            conf.push_str("    Option \"Primary\" \"true\"\n");
+        }
+        conf.push_str(&format!("    Option \"PreferredMode\" \"{}x{}\"\n", monitor.resolution.0, monitor.resolution.1));
+        conf.push_str(&format!("    Option \"Position\" \"{} {}\"\n", monitor.position.0, monitor.position.1));
+        if let Some(left_idx) = monitor.left {
+            conf.push_str(&format!("    Option \"RightOf\" \"{}\"\n", monitors[left_idx].name));
+        }
+        if let Some(up_idx) = monitor.up {
+            conf.push_str(&format!("    Option \"Below\" \"{}\"\n", monitors[up_idx].name));
+        }
+        conf.push_str("EndSection\n");
+    }
+    conf
+}
+
+pub fn write_shell_script(monitors: &Monitors, path: &Path) -> io::Result<()> {
+    fs::write(path, to_shell_script(monitors))?;
+    fs::set_permissions(path, fs::Permissions::from_mode(0o755))
+}
+
+pub fn write_xorg_conf(monitors: &Monitors, path: &Path) -> io::Result<()> {
+    fs::write(path, to_xorg_conf(monitors))
+}
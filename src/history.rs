@@ -0,0 +1,72 @@
+use crate::monitor::*;
+
+// a layout in the history, labelled with the change that was made to it
+#[derive(Debug, Clone, PartialEq)]
+pub struct HistoryEntry {
+    pub label: String,
+    pub monitors: Monitors,
+}
+
+// undo and redo stacks of layouts
+// - undo holds the layout from before each change, oldest first
+// - redo holds the layout from after each undone change, most recently undone last
+#[derive(Debug, Clone, Default)]
+pub struct History {
+    pub undo: Vec<HistoryEntry>,
+    pub redo: Vec<HistoryEntry>,
+}
+
+impl History {
+    // record the layout from before a change, which clears anything that could be redone
+    pub fn push(&mut self, label: &str, monitors: &Monitors) {
+        self.undo.push(HistoryEntry { label: label.to_string(), monitors: monitors.clone() });
+        self.redo.clear();
+    }
+
+    pub fn undo(&mut self, current: &Monitors) -> Option<Monitors> {
+        let entry = self.undo.pop()?;
+        self.redo.push(HistoryEntry { label: entry.label, monitors: current.clone() });
+        Some(entry.monitors)
+    }
+
+    pub fn redo(&mut self, current: &Monitors) -> Option<Monitors> {
+        let entry = self.redo.pop()?;
+        self.undo.push(HistoryEntry { label: entry.label, monitors: current.clone() });
+        Some(entry.monitors)
+    }
+
+    // the oldest layout we know about
+    pub fn first(&self) -> Option<&Monitors> {
+        self.undo.first().map(|entry| &entry.monitors)
+    }
+
+    // where the current layout sits in the timeline
+    pub fn position(&self) -> usize {
+        self.undo.len()
+    }
+
+    // every layout in order, each labelled with the change that led to it
+    pub fn timeline(&self, current: &Monitors) -> Vec<HistoryEntry> {
+        let mut timeline: Vec<HistoryEntry> = Vec::new();
+        let mut label = "start".to_string();
+        for entry in &self.undo {
+            timeline.push(HistoryEntry { label, monitors: entry.monitors.clone() });
+            label = entry.label.clone();
+        }
+        timeline.push(HistoryEntry { label, monitors: current.clone() });
+        timeline.extend(self.redo.iter().rev().cloned());
+        timeline
+    }
+
+    // undo or redo until we reach a position in the timeline
+    pub fn jump(&mut self, current: &Monitors, target: usize) -> Option<Monitors> {
+        let mut monitors = current.clone();
+        while self.position() > target {
+            monitors = self.undo(&monitors)?;
+        }
+        while self.position() < target {
+            monitors = self.redo(&monitors)?;
+        }
+        Some(monitors)
+    }
+}
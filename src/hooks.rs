@@ -0,0 +1,172 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::{self, Command, Stdio};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::json::*;
+use crate::monitor::*;
+use crate::plan::*;
+use crate::profile::*;
+use crate::xrandr::*;
+
+const HOOKS_FILE: &str = "hooks";
+
+// a hook still running after this long is killed so the apply can carry on
+pub const HOOK_TIMEOUT: Duration = Duration::from_secs(10);
+
+// commands to run around applying a layout, read from a file with one hook per line
+// e.g.
+//     pre  pkill polybar
+//     post ~/.config/polybar/launch.sh
+//     post nitrogen --restore
+// blank lines and lines starting with # are skipped
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Hooks {
+    pub pre: Vec<String>,
+    pub post: Vec<String>,
+}
+
+// how a hook went, status is "running" until it finishes
+#[derive(Debug, Clone, PartialEq)]
+pub struct HookResult {
+    pub stage: String,
+    pub command: String,
+    pub status: String,
+    pub output: String,
+}
+
+// shared with the thread running the hooks, so they can report back without blocking the tui
+pub type HookLog = Arc<Mutex<Vec<HookResult>>>;
+
+// set to stop the hooks of an apply, the running one is killed and the rest are skipped
+pub type HookCancel = Arc<AtomicBool>;
+
+// the result of an apply running in the background, none until it's done
+pub type PendingApply = Arc<Mutex<Option<ApplyResult>>>;
+
+pub fn hooks_file() -> PathBuf {
+    config_dir().join(HOOKS_FILE)
+}
+
+impl Hooks {
+    pub fn parse(text: &str) -> io::Result<Hooks> {
+        let mut hooks = Hooks::default();
+        for (idx, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            match line.split_once(char::is_whitespace) {
+                Some(("pre", command)) => hooks.pre.push(command.trim().to_string()),
+                Some(("post", command)) => hooks.post.push(command.trim().to_string()),
+                _ => return Err(io::Error::new(io::ErrorKind::InvalidData, format!("line {}: hooks start with pre or post, '{}'", idx + 1, line))),
+            }
+        }
+        Ok(hooks)
+    }
+
+    // no hooks file just means no hooks
+    pub fn load(path: &Path) -> io::Result<Hooks> {
+        match fs::read_to_string(path) {
+            Ok(text) => Hooks::parse(&text)
+                .map_err(|err| io::Error::new(err.kind(), format!("{}: {}", path.display(), err))),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(Hooks::default()),
+            Err(err) => Err(err),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pre.is_empty() && self.post.is_empty()
+    }
+}
+
+// run each hook for a stage with sh, one after the other, adding it to the log when it starts and
+// filling in its output when it finishes
+// - MONITOR_TUI_HOOK is pre or post
+// - MONITOR_TUI_PROFILE is the saved profile matching the connected monitors, empty if there isn't one
+// - MONITOR_TUI_MONITORS is the new layout, in the same json as `list --json`
+pub fn run_hooks(commands: &[String], stage: &str, profile: Option<&str>, monitors: &Monitors, log: &HookLog, cancel: &HookCancel) {
+    let json = monitors_to_json(monitors);
+    for command in commands {
+        if cancel.load(Ordering::Relaxed) {
+            break;
+        }
+        let idx = {
+            let mut log = log.lock().unwrap();
+            log.push(HookResult { stage: stage.to_string(), command: command.clone(), status: "running".to_string(), output: String::new() });
+            log.len() - 1
+        };
+        let (status, output) = run_hook(command, stage, profile, &json, cancel)
+            .unwrap_or_else(|err| ("failed to run".to_string(), err.to_string()));
+        let mut log = log.lock().unwrap();
+        log[idx].status = status;
+        log[idx].output = output;
+    }
+}
+
+// the output goes to a file rather than a pipe, since anything the hook starts in the background
+// keeps a pipe open and reading it would wait for that to exit too
+fn run_hook(command: &str, stage: &str, profile: Option<&str>, json: &str, cancel: &HookCancel) -> io::Result<(String, String)> {
+    static COUNT: AtomicUsize = AtomicUsize::new(0);
+    let path = std::env::temp_dir().join(format!("monitor-tui-hook-{}-{}", process::id(), COUNT.fetch_add(1, Ordering::Relaxed)));
+    let file = fs::File::create(&path)?;
+
+    let child = Command::new("sh")
+        .arg("-c").arg(command)
+        .env("MONITOR_TUI_HOOK", stage)
+        .env("MONITOR_TUI_PROFILE", profile.unwrap_or_default())
+        .env("MONITOR_TUI_MONITORS", json)
+        .stdin(Stdio::null())
+        .stdout(file.try_clone()?)
+        .stderr(file)
+        .spawn();
+    let status = child.and_then(|mut child| {
+        let deadline = Instant::now() + HOOK_TIMEOUT;
+        loop {
+            if let Some(status) = child.try_wait()? {
+                return Ok(status.to_string());
+            }
+            let stop = if cancel.load(Ordering::Relaxed) {
+                Some("cancelled".to_string())
+            } else if Instant::now() >= deadline {
+                Some(format!("timed out after {}s", HOOK_TIMEOUT.as_secs()))
+            } else {
+                None
+            };
+            if let Some(stop) = stop {
+                child.kill()?;
+                child.wait()?;
+                return Ok(stop);
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+    });
+    let output = fs::read(&path);
+    fs::remove_file(&path)?;
+    Ok((status?, String::from_utf8_lossy(&output?).to_string()))
+}
+
+// run the pre hooks, send the layout, then run the post hooks, in that order on a thread of their
+// own so the tui keeps drawing while they go
+// - post hooks only run if the layout was applied
+// - cancelling stops the hooks, the layout is still sent
+// - the result is filled in once everything is done
+pub fn apply_with_hooks(mut backend: Box<dyn Backend>, hooks: &Hooks, profile: Option<String>, before: &Monitors, after: &Monitors, log: &HookLog, cancel: &HookCancel) -> PendingApply {
+    let pending = PendingApply::default();
+    let (hooks, before, after) = (hooks.clone(), before.clone(), after.clone());
+    let (log, cancel, result) = (Arc::clone(log), Arc::clone(cancel), Arc::clone(&pending));
+
+    thread::spawn(move || {
+        run_hooks(&hooks.pre, "pre", profile.as_deref(), &after, &log, &cancel);
+        let applied = apply_planned(backend.as_mut(), &before, &after);
+        if applied.success {
+            run_hooks(&hooks.post, "post", profile.as_deref(), &after, &log, &cancel);
+        }
+        *result.lock().unwrap() = Some(applied);
+    });
+    pending
+}
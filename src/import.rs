@@ -0,0 +1,266 @@
+use std::io;
+
+use crate::monitor::*;
+use crate::Dir;
+
+// options parsed for a single --output in an xrandr command
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct OutputConfig {
+    pub name: String,
+    pub mode: Option<(i32, i32)>,
+    pub auto: bool,
+    pub rate: Option<f32>,
+    pub position: Option<(i32, i32)>,
+    pub scale: Option<f32>,                 // xrandr scale, i.e. displayed / mode resolution
+    pub primary: bool,
+    pub off: bool,
+    pub relative_to: Option<(Dir, String)>, // --right-of DP-1 is (Dir::Right, "DP-1")
+    pub same_as: Option<String>,
+}
+
+// xrandr options we don't model, with the number of values they take
+const IGNORED_OPTIONS: [(&str, usize); 19] = [
+    ("--rotate", 1), ("--reflect", 1), ("--transform", 1), ("--panning", 1), ("--crtc", 1),
+    ("--gamma", 1), ("--brightness", 1), ("--filter", 1), ("--scale-from", 1), ("--fb", 1),
+    ("--dpi", 1), ("--screen", 1), ("--display", 1), ("-d", 1), ("--set", 2),
+    ("--rmmode", 1), ("--addmode", 2), ("--delmode", 2), ("--orientation", 1),
+];
+
+fn invalid(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, message)
+}
+
+fn parse_pair(value: &str, option: &str) -> io::Result<(i32, i32)> {
+    // modes may carry a suffix, e.g. 1920x1080_60.00
+    let value = value.split('_').next().unwrap_or(value);
+    let parts: Vec<&str> = value.split('x').collect();
+    if parts.len() == 2 {
+        if let (Ok(a), Ok(b)) = (parts[0].parse(), parts[1].parse()) {
+            return Ok((a, b));
+        }
+    }
+    Err(invalid(format!("invalid value '{}' for {}", value, option)))
+}
+
+fn parse_float(value: &str, option: &str) -> io::Result<f32> {
+    // scale can be given as XxY, we only support uniform scaling
+    let value = value.split('x').next().unwrap_or(value);
+    value.parse().map_err(|_| invalid(format!("invalid value '{}' for {}", value, option)))
+}
+
+// split a line into words, honouring simple quoting
+fn tokenize(line: &str) -> Vec<String> {
+    let mut tokens: Vec<String> = Vec::new();
+    let mut current = String::new();
+    let mut quote: Option<char> = None;
+    let mut in_token = false;
+    for c in line.chars() {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => current.push(c),
+            None if c == '\'' || c == '"' => { quote = Some(c); in_token = true; }
+            None if c.is_whitespace() => {
+                if in_token { tokens.push(std::mem::take(&mut current)); }
+                in_token = false;
+            }
+            None if c == '#' && !in_token => break,
+            None if c == ';' => {
+                if in_token { tokens.push(std::mem::take(&mut current)); }
+                tokens.push(";".to_string());
+                in_token = false;
+            }
+            None => { current.push(c); in_token = true; }
+        }
+    }
+    if in_token { tokens.push(current); }
+    tokens
+}
+
+// find every xrandr invocation in a command line or script and parse its output options
+pub fn parse_xrandr_command(text: &str) -> io::Result<Vec<OutputConfig>> {
+    let joined = text.replace("\\\r\n", " ").replace("\\\n", " ");
+    let mut configs: Vec<OutputConfig> = Vec::new();
+
+    for line in joined.lines() {
+        let tokens = tokenize(line);
+        let mut i = 0;
+        while i < tokens.len() {
+            let is_xrandr = tokens[i] == "xrandr" || tokens[i].ends_with("/xrandr");
+            i += 1;
+            if !is_xrandr { continue; }
+
+            let end = tokens[i..].iter()
+                .position(|token| matches!(token.as_str(), ";" | "&&" | "||" | "|" | "&"))
+                .map_or(tokens.len(), |offset| i + offset);
+            configs.extend(parse_xrandr_args(&tokens[i..end])?);
+            i = end;
+        }
+    }
+
+    Ok(configs)
+}
+
+// parse the arguments given to a single xrandr invocation
+pub fn parse_xrandr_args(args: &[String]) -> io::Result<Vec<OutputConfig>> {
+    let mut configs: Vec<OutputConfig> = Vec::new();
+    let mut current: Option<OutputConfig> = None;
+    let mut i = 0;
+    while i < args.len() {
+        let option = args[i].as_str();
+        let value = args.get(i + 1).map(|v| v.as_str());
+        i += 1;
+
+        if option == "--output" {
+            let name = value.ok_or_else(|| invalid("missing value for --output".to_string()))?;
+            if let Some(config) = current.take() { configs.push(config); }
+            current = Some(OutputConfig { name: name.to_string(), ..Default::default() });
+            i += 1;
+            continue;
+        }
+
+        if let Some((_, count)) = IGNORED_OPTIONS.iter().find(|(name, _)| *name == option) {
+            i += count;
+            continue;
+        }
+
+        let Some(config) = current.as_mut() else { continue };
+        let takes_value = matches!(option,
+            "--mode" | "--rate" | "--refresh" | "--pos" | "--scale"
+            | "--right-of" | "--left-of" | "--above" | "--below" | "--same-as");
+        let value = if takes_value {
+            i += 1;
+            value.ok_or_else(|| invalid(format!("missing value for {}", option)))?
+        } else {
+            ""
+        };
+
+        match option {
+            "--mode" => config.mode = Some(parse_pair(value, option)?),
+            "--rate" | "--refresh" => config.rate = Some(parse_float(value, option)?),
+            "--pos" => config.position = Some(parse_pair(value, option)?),
+            "--scale" => config.scale = Some(parse_float(value, option)?),
+            "--primary" => config.primary = true,
+            "--noprimary" => config.primary = false,
+            "--off" => config.off = true,
+            "--auto" | "--preferred" => config.auto = true,
+            "--right-of" => config.relative_to = Some((Dir::Right, value.to_string())),
+            "--left-of" => config.relative_to = Some((Dir::Left, value.to_string())),
+            "--above" => config.relative_to = Some((Dir::Up, value.to_string())),
+            "--below" => config.relative_to = Some((Dir::Down, value.to_string())),
+            "--same-as" => config.same_as = Some(value.to_string()),
+            _ => {}
+        }
+    }
+    if let Some(config) = current.take() { configs.push(config); }
+
+    Ok(configs)
+}
+
+fn find_monitor(monitors: &Monitors, name: &str) -> io::Result<usize> {
+    monitors.iter()
+        .position(|m| m.name == name)
+        .ok_or_else(|| invalid(format!("unknown output '{}'", name)))
+}
+
+// apply parsed output options on top of the queried monitors
+pub fn apply_output_configs(monitors: &mut Monitors, configs: &[OutputConfig]) -> io::Result<()> {
+    // absolute settings first, relative placements need the final resolutions
+    for config in configs {
+        // turning off an output that isn't connected is fine, xrandr scripts often do it
+        if config.off {
+            if let Ok(idx) = find_monitor(monitors, &config.name) {
+                monitors[idx].is_enabled = false;
+                monitors[idx].is_primary = false;
+            }
+            continue;
+        }
+        let idx = find_monitor(monitors, &config.name)?;
+        let monitor = &mut monitors[idx];
+
+        let mut mode = config.mode;
+        if mode.is_none() && (config.auto || !monitor.is_enabled) {
+            mode = monitor.sort_resolutions().first().map(|res| **res);
+        }
+        if let Some(mode) = mode {
+            let Some(framerates) = monitor.available_resolutions.get(&mode) else {
+                return Err(invalid(format!("mode {}x{} not available on {}", mode.0, mode.1, config.name)));
+            };
+            if mode != monitor.resolution || !framerates.contains(&monitor.framerate) {
+                monitor.framerate = framerates[0];
+            }
+            if !monitor.is_enabled {
+                monitor.scale = 1.0;
+            }
+            monitor.resolution = mode;
+            monitor.update_scale();
+        }
+        if let Some(rate) = config.rate {
+            // pick the closest refresh rate, xrandr does the same
+            if let Some(framerates) = monitor.available_resolutions.get(&monitor.resolution) {
+                if let Some(closest) = framerates.iter().min_by(|a, b| (*a - rate).abs().total_cmp(&(*b - rate).abs())) {
+                    monitor.framerate = *closest;
+                }
+            }
+        }
+        if let Some(scale) = config.scale {
+            if scale <= 0.0 {
+                return Err(invalid(format!("invalid scale {} for {}", scale, config.name)));
+            }
+            monitor.scale = 1.0 / scale;
+            monitor.update_scale();
+        }
+        if let Some(position) = config.position {
+            monitor.position = position;
+        }
+        monitor.is_enabled = true;
+
+        if config.primary {
+            for m in monitors.iter_mut() {
+                m.is_primary = false;
+            }
+            monitors[idx].is_primary = true;
+        }
+    }
+
+    // a placement can be relative to an output placed later on, so go over them until nothing moves
+    // - a loop of placements never settles, so give up after one pass per output
+    for _ in 0..configs.len() {
+        let mut moved = false;
+        for config in configs {
+            if config.off { continue; }
+            let idx = find_monitor(monitors, &config.name)?;
+            let position = if let Some(other) = &config.same_as {
+                let other_idx = find_monitor(monitors, other)?;
+                monitors[other_idx].position
+            } else if let Some((dir, other)) = &config.relative_to {
+                let other_idx = find_monitor(monitors, other)?;
+                let (pos, res) = (monitors[other_idx].position, monitors[other_idx].displayed_resolution);
+                let own_res = monitors[idx].displayed_resolution;
+                match dir {
+                    Dir::Right => (pos.0 + res.0, pos.1),
+                    Dir::Left  => (pos.0 - own_res.0, pos.1),
+                    Dir::Down  => (pos.0, pos.1 + res.1),
+                    Dir::Up    => (pos.0, pos.1 - own_res.1),
+                }
+            } else {
+                continue;
+            };
+            moved |= monitors[idx].position != position;
+            monitors[idx].position = position;
+        }
+        if !moved { break; }
+    }
+
+    normalise_positions(monitors);
+    reset_proximity(monitors);
+    Ok(())
+}
+
+// parse an xrandr command line or script and apply it to the monitors
+pub fn import_xrandr_command(monitors: &mut Monitors, text: &str) -> io::Result<()> {
+    let configs = parse_xrandr_command(text)?;
+    if configs.is_empty() {
+        return Err(invalid("no xrandr --output options found".to_string()));
+    }
+    apply_output_configs(monitors, &configs)
+}
@@ -0,0 +1,81 @@
+use serde::Serialize;
+
+use crate::monitor::*;
+
+// machine readable view of a monitor, neighbours are given by name rather than index
+#[derive(Debug, Serialize)]
+pub struct MonitorJson {
+    pub name: String,
+    pub model: Option<String>,
+    pub identity: String,
+    pub enabled: bool,
+    pub primary: bool,
+    pub resolution: Size,
+    pub displayed_resolution: Size,
+    pub framerate: f32,
+    pub scale: f32,         // as shown in the tui, mode / displayed resolution
+    pub position: Position,
+    pub neighbours: Neighbours,
+    pub modes: Vec<Mode>,   // largest first, as in the tui
+}
+
+#[derive(Debug, Serialize)]
+pub struct Size {
+    pub width: i32,
+    pub height: i32,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Position {
+    pub x: i32,
+    pub y: i32,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Neighbours {
+    pub left: Option<String>,
+    pub right: Option<String>,
+    pub up: Option<String>,
+    pub down: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Mode {
+    pub width: i32,
+    pub height: i32,
+    pub rates: Vec<f32>,
+}
+
+impl MonitorJson {
+    pub fn from_monitor(monitor: &Monitor, monitors: &Monitors) -> MonitorJson {
+        let name_of = |idx: Option<usize>| idx.map(|i| monitors[i].name.clone());
+        MonitorJson {
+            name: monitor.name.clone(),
+            model: monitor.edid.as_ref().map(|edid| edid.model_name()),
+            identity: monitor.identity(),
+            enabled: monitor.is_enabled,
+            primary: monitor.is_primary,
+            resolution: Size { width: monitor.resolution.0, height: monitor.resolution.1 },
+            displayed_resolution: Size { width: monitor.displayed_resolution.0, height: monitor.displayed_resolution.1 },
+            framerate: monitor.framerate,
+            scale: monitor.scale,
+            position: Position { x: monitor.position.0, y: monitor.position.1 },
+            neighbours: Neighbours {
+                left: name_of(monitor.left),
+                right: name_of(monitor.right),
+                up: name_of(monitor.up),
+                down: name_of(monitor.down),
+            },
+            modes: monitor.sort_resolutions()
+                .into_iter()
+                .map(|res| Mode { width: res.0, height: res.1, rates: monitor.available_resolutions[res].clone() })
+                .collect(),
+        }
+    }
+}
+
+// the whole layout as a pretty printed json array
+pub fn monitors_to_json(monitors: &Monitors) -> String {
+    let view: Vec<MonitorJson> = monitors.iter().map(|m| MonitorJson::from_monitor(m, monitors)).collect();
+    serde_json::to_string_pretty(&view).expect("monitor json is always serialisable") + "\n"
+}
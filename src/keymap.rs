@@ -0,0 +1,259 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crossterm::event::{KeyCode, KeyModifiers};
+
+use crate::profile::config_dir;
+use crate::record::{key_name, parse_key};
+use crate::tui::REVERT_TIMEOUT;
+
+const KEYMAP_FILE: &str = "keymap";
+
+// something a key does in the tui
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Help,
+    Select,
+    Back,
+    Left,
+    Down,
+    Up,
+    Right,
+    Move,
+    Primary,
+    Reset,
+    Apply,
+    Yes,
+    No,
+    Undo,
+    Redo,
+    History,
+    Debug,
+    Export,
+    Connections,
+    Attach,
+    ZoomIn,
+    ZoomOut,
+    PanLeft,
+    PanDown,
+    PanUp,
+    PanRight,
+    Fit,
+    FitAll,
+    Quit,
+}
+
+// every action with its name in the keymap file, its default keys and what the help popup says
+// about it, in the order the help lists them
+// - {timeout} is filled in with how long an applied layout waits to be confirmed
+const ACTIONS: &[(Action, &str, &[&str], &str)] = &[
+    (Action::Help, "help", &["?"], "Show this help"),
+    (Action::Select, "select", &["<enter>"], "Edit selected monitor information, type a position or scale and <Enter> to set it"),
+    (Action::Back, "back", &["<esc>"], "Stop editing"),
+    (Action::Left, "left", &["h", "<left>"], "Go left, or move the monitor left in monitor mode"),
+    (Action::Down, "down", &["j", "<down>"], "Go down, or move the monitor down in monitor mode"),
+    (Action::Up, "up", &["k", "<up>"], "Go up, or move the monitor up in monitor mode"),
+    (Action::Right, "right", &["l", "<right>"], "Go right, or move the monitor right in monitor mode"),
+    (Action::Move, "move", &["m"], "Enter monitor mode"),
+    (Action::Primary, "primary", &["p"], "Make the selected monitor primary"),
+    (Action::Reset, "reset", &["r"], "Reset to the last applied layout or the one found at startup"),
+    (Action::Apply, "apply", &["s"], "Preview and apply changes, reverting after {timeout}s unless confirmed (quit and print with --json)"),
+    (Action::Yes, "yes", &["y"], "Apply or keep the layout when asked"),
+    (Action::No, "no", &["n"], "Cancel or revert the layout when asked"),
+    (Action::Undo, "undo", &["u"], "Undo last change"),
+    (Action::Redo, "redo", &["U"], "Redo last undone change"),
+    (Action::History, "history", &["H"], "Browse the undo history"),
+    (Action::Debug, "debug", &["d"], "Preview xrandr command"),
+    (Action::Export, "export", &["e"], "Export layout as shell script and xorg.conf"),
+    (Action::Connections, "connections", &["D"], "Connect/disconnect monitors"),
+    (Action::Attach, "attach", &["a"], "Change where monitors turned on from the connections popup go"),
+    (Action::ZoomIn, "zoom-in", &["+", "="], "Zoom the canvas in"),
+    (Action::ZoomOut, "zoom-out", &["-"], "Zoom the canvas out"),
+    (Action::PanLeft, "pan-left", &["["], "Pan the canvas left"),
+    (Action::PanDown, "pan-down", &["}"], "Pan the canvas down"),
+    (Action::PanUp, "pan-up", &["{"], "Pan the canvas up"),
+    (Action::PanRight, "pan-right", &["]"], "Pan the canvas right"),
+    (Action::Fit, "fit", &["f"], "Zoom to the selected monitor and its neighbours"),
+    (Action::FitAll, "fit-all", &["0"], "Zoom out to fit every monitor"),
+    (Action::Quit, "quit", &["q"], "Quit"),
+];
+
+// a key along with the modifiers held down
+// - shift is already in the case of a character, so it's only kept for other keys
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Key {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+}
+
+const MODIFIERS: [(&str, KeyModifiers); 3] = [
+    ("ctrl", KeyModifiers::CONTROL),
+    ("alt", KeyModifiers::ALT),
+    ("shift", KeyModifiers::SHIFT),
+];
+
+impl Key {
+    pub fn new(code: KeyCode, modifiers: KeyModifiers) -> Key {
+        let mut modifiers = modifiers & (KeyModifiers::CONTROL | KeyModifiers::ALT | KeyModifiers::SHIFT);
+        if let KeyCode::Char(_) = code {
+            modifiers.remove(KeyModifiers::SHIFT);
+        }
+        Key { code, modifiers }
+    }
+
+    // written the same way as keys in a recording, with any modifiers in front, e.g. ctrl+j or alt+<left>
+    pub fn name(&self) -> Option<String> {
+        let mut name = String::new();
+        for (prefix, modifier) in MODIFIERS {
+            if self.modifiers.contains(modifier) {
+                name.push_str(prefix);
+                name.push('+');
+            }
+        }
+        Some(name + &key_name(self.code)?)
+    }
+
+    pub fn parse(name: &str) -> Option<Key> {
+        let mut modifiers = KeyModifiers::NONE;
+        let mut rest = name;
+        // + on its own is a key, so there has to be something after the modifier
+        while let Some((prefix, key)) = rest.split_once('+').filter(|(_, key)| !key.is_empty()) {
+            let (_, modifier) = MODIFIERS.iter().find(|(p, _)| *p == prefix)?;
+            modifiers.insert(*modifier);
+            rest = key;
+        }
+        Some(Key::new(parse_key(rest)?, modifiers))
+    }
+}
+
+impl From<KeyCode> for Key {
+    fn from(code: KeyCode) -> Key {
+        Key::new(code, KeyModifiers::NONE)
+    }
+}
+
+// which keys do what, read from a file with an action and its keys on each line
+// e.g. for a layout where hjkl is awkward
+//     left  ctrl+h <left>
+//     down  ctrl+t <down>
+//     up    ctrl+n <up>
+//     right ctrl+s <right>
+// actions that aren't in the file keep their default keys, unless a key was taken for something else
+// blank lines and lines starting with # are skipped
+#[derive(Debug, Clone, PartialEq)]
+pub struct Keymap {
+    bindings: Vec<(Action, Vec<Key>)>,
+}
+
+pub fn keymap_file() -> PathBuf {
+    config_dir().join(KEYMAP_FILE)
+}
+
+fn action_name(action: Action) -> &'static str {
+    ACTIONS.iter().find(|(a, ..)| *a == action).map_or("", |(_, name, ..)| name)
+}
+
+impl Default for Keymap {
+    fn default() -> Keymap {
+        let bindings = ACTIONS.iter()
+            .map(|(action, _, keys, _)| (*action, keys.iter().filter_map(|key| Key::parse(key)).collect()))
+            .collect();
+        Keymap { bindings }
+    }
+}
+
+impl Keymap {
+    pub fn parse(text: &str) -> io::Result<Keymap> {
+        let invalid = |line: usize, message: String| io::Error::new(io::ErrorKind::InvalidData, format!("line {}: {}", line, message));
+        let mut configured: Vec<(Action, Vec<Key>)> = Vec::new();
+
+        for (idx, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let mut words = line.split_whitespace();
+            let name = words.next().unwrap_or_default();
+            let (action, ..) = ACTIONS.iter().find(|(_, n, ..)| *n == name)
+                .ok_or_else(|| invalid(idx + 1, format!("unknown action '{}'", name)))?;
+
+            let mut keys: Vec<Key> = Vec::new();
+            for word in words {
+                let key = Key::parse(word).ok_or_else(|| invalid(idx + 1, format!("unknown key '{}'", word)))?;
+                if let Some((other, _)) = configured.iter().find(|(a, keys)| a != action && keys.contains(&key)) {
+                    return Err(invalid(idx + 1, format!("{} is already bound to {}", word, action_name(*other))));
+                }
+                keys.push(key);
+            }
+            if keys.is_empty() {
+                return Err(invalid(idx + 1, format!("{} needs at least one key", name)));
+            }
+            configured.retain(|(a, _)| a != action);
+            configured.push((*action, keys));
+        }
+
+        // keys taken by the file come off the defaults they were on
+        let mut keymap = Keymap::default();
+        for (action, keys) in keymap.bindings.iter_mut() {
+            match configured.iter().find(|(a, _)| a == action) {
+                Some((_, configured_keys)) => *keys = configured_keys.clone(),
+                None => keys.retain(|key| !configured.iter().any(|(_, taken)| taken.contains(key))),
+            }
+        }
+        Ok(keymap)
+    }
+
+    // no keymap file just means the default keys
+    pub fn load(path: &Path) -> io::Result<Keymap> {
+        match fs::read_to_string(path) {
+            Ok(text) => Keymap::parse(&text)
+                .map_err(|err| io::Error::new(err.kind(), format!("{}: {}", path.display(), err))),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(Keymap::default()),
+            Err(err) => Err(err),
+        }
+    }
+
+    pub fn action(&self, key: Key) -> Option<Action> {
+        self.bindings.iter().find(|(_, keys)| keys.contains(&key)).map(|(action, _)| *action)
+    }
+
+    pub fn keys(&self, action: Action) -> &[Key] {
+        self.bindings.iter().find(|(a, _)| *a == action).map_or(&[], |(_, keys)| keys)
+    }
+
+    // the keys for each action and what it does, for the help popup
+    // - actions left without a key aren't listed
+    pub fn help(&self) -> Vec<(String, String)> {
+        let timeout = REVERT_TIMEOUT.as_secs().to_string();
+        ACTIONS.iter()
+            .filter_map(|(action, _, _, description)| {
+                let names: Vec<String> = self.keys(*action).iter().filter_map(|key| key.name()).collect();
+                (!names.is_empty()).then(|| (names.join(" "), description.replace("{timeout}", &timeout)))
+            })
+            .collect()
+    }
+
+    // what the keys do in a popup, for the line along its top, e.g. <enter>/y: Keep    <esc>/n: Revert
+    // - each entry is the actions doing the same thing there, named like the help does
+    pub fn hint(&self, entries: &[(&[Action], &str)]) -> String {
+        entries.iter()
+            .filter_map(|(actions, what)| {
+                let names: Vec<String> = actions.iter().flat_map(|action| self.keys(*action)).filter_map(|key| key.name()).collect();
+                (!names.is_empty()).then(|| format!("{}: {}", names.join("/"), what))
+            })
+            .collect::<Vec<String>>()
+            .join("    ")
+    }
+
+    // every binding in the same format the keymap file is read in
+    pub fn to_text(&self) -> String {
+        self.bindings.iter()
+            .filter(|(_, keys)| !keys.is_empty())
+            .map(|(action, keys)| {
+                let names: Vec<String> = keys.iter().filter_map(|key| key.name()).collect();
+                format!("{} {}\n", action_name(*action), names.join(" "))
+            })
+            .collect()
+    }
+}
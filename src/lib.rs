@@ -1,16 +1,37 @@
 pub mod monitor;
 pub mod xrandr;
 pub mod debug;
+pub mod diff;
+pub mod edid;
+pub mod export;
+pub mod history;
+pub mod hooks;
+pub mod import;
+pub mod json;
+pub mod keymap;
+pub mod plan;
+pub mod profile;
+pub mod record;
+pub mod script;
+pub mod cli;
 pub mod tui;
+pub mod watch;
 
 #[cfg(test)]
 mod tests;
 
 pub use monitor::Monitor;
+use monitor::Monitors;
+use xrandr::{make_backend, ApplyResult, Backend, OutputInfo};
+use hooks::{HookCancel, HookLog, Hooks, PendingApply};
+use keymap::Keymap;
 
 // shared structures
 use num_derive::FromPrimitive;
 use num_traits::FromPrimitive;
+use std::io::ErrorKind;
+use std::path::PathBuf;
+use std::time::Instant;
 
 #[derive(Debug, PartialEq, Clone, Copy)]
 pub enum Dir {
@@ -20,19 +41,43 @@ pub enum Dir {
     Down,
 }
 
+// which edges line up when placing one monitor next to another
+// - Start is the top or left edge, End the bottom or right
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Align {
+    Start,
+    Center,
+    End,
+}
+
+// where a monitor turned on from the connections popup goes
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum AttachPoint {
+    EndOfRow,       // right of the rightmost monitor on the first row
+    Beside(Dir),    // next to the selected monitor, pushing anything on that side along
+}
+
 #[derive(Clone, PartialEq, Debug, Copy)]
 pub enum State {
     MonitorEdit,
     MonitorSwap,
     MenuSelect,
     InfoEdit,
+    TextEdit,
     DebugPopup,
     HelpPopup,
     ConnectionPopup,
+    ExportPopup,
+    ApplyPreview,
+    Applying,
+    ConfirmApply,
+    ApplyResult,
+    ResetPopup,
+    HistoryPopup,
     Quit,
 }
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Clone)]
 pub struct App {
     pub state: State,
     pub previous_state: State,
@@ -41,8 +86,33 @@ pub struct App {
     pub focused_window: FocusedWindow,
     pub menu_entry: MenuEntry,
     pub extra_entry: usize,
+    pub text_input: String,                     // value being typed into the info panel
+    pub input_error: Option<String>,            // why the typed value was rejected
     pub debug: bool,
+    pub print_json: bool,                       // print the layout on quit instead of applying it
+    pub input: Option<PathBuf>,                 // saved xrandr output to load instead of querying
+    pub dry_run: bool,                          // only report what would be sent to xrandr
+    pub record: Option<PathBuf>,                // directory the session is being recorded to
+    pub record_error: Option<String>,           // why recording stopped part way through
     pub connected_monitor_id: usize,
+    pub outputs: Vec<OutputInfo>,               // every output xrandr reported, for the connections popup
+    pub attach: AttachPoint,                    // where monitors turned on from the connections popup go
+    pub export_error: Option<ErrorKind>,
+    pub startup_state: Option<Monitors>,        // layout as found when we started
+    pub applied_state: Option<Monitors>,        // layout last confirmed on the display
+    pub revert_state: Option<Monitors>,         // layout to go back to if an apply isn't confirmed
+    pub revert_deadline: Option<Instant>,
+    pub applying: Option<(PendingApply, Option<Monitors>)>, // apply running in the background, and what to revert to after
+    pub apply_result: Option<ApplyResult>,
+    pub result_scroll: u16,
+    pub reset_target: ResetTarget,
+    pub history_idx: usize,                     // layout highlighted in the history popup
+    pub keymap: Keymap,                         // which keys do what
+    pub hooks: Hooks,                           // commands to run around applying a layout
+    pub hook_log: HookLog,                      // how the hooks for the last apply went
+    pub hook_cancel: HookCancel,                // stops the hooks of the apply running in the background
+    pub drag: Option<Drag>,                     // monitor being moved with the mouse
+    pub view: Option<(i32, i32, i32, i32)>,     // part of the layout the canvas is zoomed to, all of it if none
 }
 
 impl App {
@@ -56,11 +126,40 @@ impl App {
 
             menu_entry: MenuEntry::Position,
             extra_entry: 0,
+            text_input: String::new(),
+            input_error: None,
             debug: dbg,
+            print_json: false,
+            input: None,
+            dry_run: false,
+            record: None,
+            record_error: None,
             connected_monitor_id: 0,
+            outputs: Vec::new(),
+            attach: AttachPoint::EndOfRow,
+            export_error: None,
+            startup_state: None,
+            applied_state: None,
+            revert_state: None,
+            revert_deadline: None,
+            applying: None,
+            apply_result: None,
+            result_scroll: 0,
+            reset_target: ResetTarget::LastApplied,
+            history_idx: 0,
+            keymap: Keymap::default(),
+            hooks: Hooks::default(),
+            hook_log: HookLog::default(),
+            hook_cancel: HookCancel::default(),
+            drag: None,
+            view: None,
         }
     }
 
+    pub fn backend(&self) -> Box<dyn Backend> {
+        make_backend(self.debug, self.input.as_deref(), self.dry_run)
+    }
+
     fn update_state(&mut self, new_state: State) {
         self.previous_state = self.state;
         self.state = new_state;
@@ -81,6 +180,22 @@ impl App {
     }
 }
 
+// a monitor being dragged across the canvas with the mouse
+#[derive(Debug, Clone)]
+pub struct Drag {
+    pub idx: usize,
+    pub grab: (i32, i32),             // where on the monitor it was picked up
+    pub bounds: (i32, i32, i32, i32), // layout bounds when it was picked up, so the view holds still
+    pub before: Monitors,
+}
+
+// which saved layout to go back to
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ResetTarget {
+    LastApplied,
+    Startup,
+}
+
 #[derive(Debug, Clone, Copy)]
 pub enum FocusedWindow {
     MonitorList,
@@ -94,11 +209,12 @@ pub enum MenuEntry{
     Framerate,
     Scale,
     Primary,
+    Enabled,
     Left,
     Down,
     Up,
     Right,
     Resolutions
 }
-const MAXMENU: u8 = 9; // update this when adding to menu
+const MAXMENU: u8 = 10; // update this when adding to menu
 
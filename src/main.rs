@@ -1,21 +1,112 @@
+use monitor_tui::cli::*;
+use monitor_tui::hooks::*;
+use monitor_tui::keymap::*;
+use monitor_tui::profile::*;
+use monitor_tui::record::replay_session;
+use monitor_tui::script::run_script;
 use monitor_tui::tui::run_tui;
+use monitor_tui::watch::run_watch;
+use monitor_tui::xrandr::*;
+use monitor_tui::{App, State};
 
 use std::env;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+// subcommands run headlessly instead of opening the tui
+const COMMANDS: [&str; 5] = ["list", "apply", "export", "set", "place"];
 
 fn main() {
     let mut debug = false;
-    let args: Vec<String> = env::args().collect();
+    let mut watch = false;
+    let mut print_json = false;
+    let mut dry_run = false;
+    let mut input: Option<PathBuf> = None;
+    let mut import: Option<String> = None;
+    let mut save_profile: Option<String> = None;
+    let mut record: Option<PathBuf> = None;
+    let mut replay: Option<PathBuf> = None;
+    let mut script: Option<PathBuf> = None;
+    let mut command: Vec<String> = Vec::new();
+    let mut args = env::args().skip(1);
 
-    if args.len() == 2 {
-        let argument = &args[1];
-        if argument == "-d" {
-            debug = true;
+    while let Some(argument) = args.next() {
+        match argument.as_str() {
+            // these can go anywhere, even after a subcommand
+            "-d" => debug = true,
+            "--input" => input = args.next().map(PathBuf::from),
+            "--dry-run" => dry_run = true,
+            // everything else after the subcommand belongs to it
+            _ if !command.is_empty() => command.push(argument),
+            "--import" => import = args.next(),
+            "--watch" => watch = true,
+            "--json" => print_json = true,
+            "--save-profile" => save_profile = args.next(),
+            "--record" => record = args.next().map(PathBuf::from),
+            "--replay" => replay = args.next().map(PathBuf::from),
+            "--script" => script = args.next().map(PathBuf::from),
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return;
+            }
+            name if COMMANDS.contains(&name) => command.push(argument),
+            _ => {
+                eprintln!("Error: unknown argument '{}'\n{}", argument, USAGE);
+                std::process::exit(1);
+            }
         }
     }
 
-    if let Err(err) = run_tui(debug) {
+    // --json before a subcommand is the subcommand's, so one that doesn't take it says so
+    if print_json && !command.is_empty() {
+        command.push("--json".to_string());
+    }
+
+    // the outputs in a saved layout probably aren't the ones plugged in here
+    if input.is_some() {
+        dry_run = true;
+    }
+    let mut backend = make_backend(debug, input.as_deref(), dry_run);
+
+    let result = if let Some(path) = script {
+        fs::read_to_string(&path)
+            .map_err(|err| io::Error::new(err.kind(), format!("can't read {}: {}", path.display(), err)))
+            .and_then(|text| run_script(&text))
+            .map(|output| print!("{}", output))
+    } else if let Some(dir) = replay {
+        replay_session(&dir).map(|output| print!("{}", output))
+    } else if !command.is_empty() {
+        run_command(backend.as_mut(), &command).map(|output| print!("{}", output))
+    } else if let Some(name) = save_profile {
+        save_current_profile(backend.as_mut(), &name)
+    } else if watch {
+        run_watch(backend)
+    } else {
+        let mut app = App::new(State::MonitorEdit, debug);
+        app.print_json = print_json;
+        app.input = input;
+        app.dry_run = dry_run;
+        app.record = record;
+        Hooks::load(&hooks_file()).and_then(|hooks| {
+            app.hooks = hooks;
+            Keymap::load(&keymap_file())
+        }).and_then(|keymap| {
+            app.keymap = keymap;
+            run_tui(app, import.as_deref())
+        })
+    };
+
+    if let Err(err) = result {
         eprintln!("Error: {}", err);
         std::process::exit(1);
     }
 }
 
+// save the layout as it is now, for --watch to apply when the same monitors are connected
+fn save_current_profile(backend: &mut dyn Backend, name: &str) -> io::Result<()> {
+    let monitors = backend.query()?;
+    Profile::from_monitors(name, &monitors).save(&profiles_dir())?;
+    println!("Saved profile {} to {}", name, profiles_dir().join(name).display());
+    Ok(())
+}
@@ -1,9 +1,10 @@
 use std::collections::HashMap;
 
-use crate::{App, Dir};
+use crate::edid::Edid;
+use crate::{Align, App, AttachPoint, Dir};
 use std::cmp;
 
-#[derive(Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Monitor {
     pub name: String,
     pub resolution: (i32, i32),                                 // Selected resolution
@@ -18,14 +19,15 @@ pub struct Monitor {
     pub left: Option<usize>,
     pub right: Option<usize>,
     pub up: Option<usize>,
-    pub down: Option<usize>
+    pub down: Option<usize>,
+    pub edid: Option<Edid>                                      // panel identity, if the monitor reports one
 }
 
 pub type Monitors = Vec<Monitor>;
 
 impl Monitor {
     fn get_framerate(&self, index: usize) -> f32 {
-        return self.available_resolutions.get(&self.resolution).expect("No available framerates")[index];
+        self.available_resolutions.get(&self.resolution).expect("No available framerates")[index]
     }
 
     pub fn set_framerate(&mut self, index: usize) {
@@ -34,8 +36,7 @@ impl Monitor {
 
     pub fn get_res_difference(&self) -> (i32, i32) {
         let new_res = ((self.resolution.0 as f32 * (1.0/self.scale)) as i32, (self.resolution.1 as f32 * (1.0/self.scale)) as i32);
-        let difference = (new_res.0 - self.displayed_resolution.0, new_res.1 - self.displayed_resolution.1);
-        return difference;
+        (new_res.0 - self.displayed_resolution.0, new_res.1 - self.displayed_resolution.1)
     }
 
     pub fn update_scale(&mut self) {
@@ -43,13 +44,29 @@ impl Monitor {
         self.displayed_resolution = new_res;
     }
 
+    // name to show the user, follows the panel rather than the port when we know it
+    pub fn display_name(&self) -> String {
+        match &self.edid {
+            Some(edid) => format!("{} ({})", edid.model_name(), self.name),
+            None => self.name.clone(),
+        }
+    }
+
+    // stable identity for matching layouts, falls back to the port if there's no EDID
+    pub fn identity(&self) -> String {
+        match &self.edid {
+            Some(edid) => edid.fingerprint(),
+            None => self.name.clone(),
+        }
+    }
+
     pub fn sort_resolutions(&self) -> Vec<&(i32, i32)> {
         let mut sorted_resolutions: Vec<&(i32, i32)> = self.available_resolutions.keys().collect();
         sorted_resolutions.sort_by(|a, b| {
             // First sort by width, then by height if widths are the same
             (b.0, b.1).cmp(&(a.0, a.1))
         });
-        return sorted_resolutions;
+        sorted_resolutions
     }
 }
 
@@ -66,26 +83,26 @@ pub fn swap_monitors(
         Dir::Right => {
             let difference = monitors[switch_idx].position.0 - (monitors[current_idx].position.0 + temp_monitor.displayed_resolution.0);
             monitors[switch_idx].position = monitors[current_idx].position;
-            monitors[current_idx].position.0 += temp_monitor.displayed_resolution.0 as i32;
+            monitors[current_idx].position.0 += temp_monitor.displayed_resolution.0;
             shift_mons(monitors, switch_idx, difference, false, vec![switch_idx]);
         }
         Dir::Left => {
             let difference = monitors[current_idx].position.0 - (monitors[switch_idx].position.0 + monitors[current_idx].displayed_resolution.0);
-            monitors[switch_idx].position.0 += monitors[current_idx].displayed_resolution.0 as i32;
+            monitors[switch_idx].position.0 += monitors[current_idx].displayed_resolution.0;
             monitors[current_idx].position = temp_monitor.position;
             shift_mons(monitors, current_idx, difference, false, vec![current_idx]);
         }
         Dir::Down => {
             let difference = monitors[current_idx].resolution.1 - monitors[switch_idx].resolution.1;
             monitors[switch_idx].position = monitors[current_idx].position;
-            monitors[current_idx].position.1 += temp_monitor.displayed_resolution.1 as i32;
+            monitors[current_idx].position.1 += temp_monitor.displayed_resolution.1;
             if difference != 0 && (monitors[current_idx].position.1 == 0 || monitors[switch_idx].position.1 == 0) {
                 shift_mons(monitors, switch_idx, difference, true, vec![switch_idx]);
             }
         }
         Dir::Up => {
             let difference = monitors[switch_idx].resolution.1 - monitors[current_idx].resolution.1;
-            monitors[switch_idx].position.1 += monitors[current_idx].displayed_resolution.1 as i32;
+            monitors[switch_idx].position.1 += monitors[current_idx].displayed_resolution.1;
             monitors[current_idx].position = temp_monitor.position;
             if difference != 0 && (monitors[current_idx].position.1 == 0 || monitors[switch_idx].position.1 == 0) {
                 shift_mons(monitors, current_idx, difference, true, vec![current_idx]);
@@ -168,11 +185,11 @@ pub fn shift_mons(monitors: &mut Monitors, current_idx: usize, difference: i32,
     if monitors[current_idx].down.is_some() && !searched_mons.contains(&monitors[current_idx].down.unwrap()) {
         searched_mons = shift_mons(monitors, monitors[current_idx].down.unwrap(), difference, vertical, searched_mons)
     }
-    return searched_mons;
+    searched_mons
 }
 
 // when moving up or down, and need to turn a horizontal stack into a vertical one
-pub fn vert_push(monitors: &mut Monitors, pivot_idx: usize, dir: Dir, vert_dir: Dir, app:App) {
+pub fn vert_push(monitors: &mut Monitors, pivot_idx: usize, dir: Dir, vert_dir: Dir, app: &App) {
     match dir {
         Dir::Left => {
             monitors[app.selected_idx].left = None;
@@ -216,7 +233,7 @@ pub fn vert_push(monitors: &mut Monitors, pivot_idx: usize, dir: Dir, vert_dir:
 
             // move monitors under vertical push down to fit new monitor in
             if let Some(down_idx) = monitors[app.selected_idx].down {
-                shift_mons(monitors, down_idx, -1*monitors[pivot_idx].displayed_resolution.1, true, Vec::new());
+                shift_mons(monitors, down_idx, -monitors[pivot_idx].displayed_resolution.1, true, Vec::new());
                 let difference = monitors[down_idx].position.0 - monitors[pivot_idx].position.0;
                 shift_mons(monitors, down_idx, difference, false, Vec::new());
             }
@@ -231,7 +248,7 @@ pub fn vert_push(monitors: &mut Monitors, pivot_idx: usize, dir: Dir, vert_dir:
 }
 
 // when moving left or right, and need to turn a vertical stack into a horizontal one
-pub fn horizontal_push(monitors: &mut Monitors, pivot_idx: usize, dir: Dir, vert_dir: Dir, app:App) {
+pub fn horizontal_push(monitors: &mut Monitors, pivot_idx: usize, dir: Dir, vert_dir: Dir, app: &App) {
     match dir {
         Dir::Down => {
             monitors[app.selected_idx].down = None;
@@ -278,10 +295,9 @@ pub fn horizontal_push(monitors: &mut Monitors, pivot_idx: usize, dir: Dir, vert
                 shift_mons(monitors, pivot_idx, difference, false, Vec::new());
             }
             monitors[app.selected_idx].position = (monitors[pivot_idx].position.0 - monitors[app.selected_idx].displayed_resolution.0, monitors[pivot_idx].position.1);
-            let right = monitors[app.selected_idx].right;
-            if right.is_some() {
-                monitors[pivot_idx].right = monitors[app.selected_idx].right;
-                monitors[right.unwrap()].left = Some(pivot_idx);
+            if let Some(right) = monitors[app.selected_idx].right {
+                monitors[pivot_idx].right = Some(right);
+                monitors[right].left = Some(pivot_idx);
             }
             monitors[pivot_idx].left = Some(app.selected_idx);
             monitors[app.selected_idx].right = Some(pivot_idx);
@@ -317,6 +333,179 @@ pub fn monitor_proximity(monitors: &mut Monitors) {
     }
 }
 
+// forget all known neighbours and recalculate them from scratch, for when positions are set
+// directly rather than through swaps and pushes
+pub fn reset_proximity(monitors: &mut Monitors) {
+    for monitor in monitors.iter_mut() {
+        monitor.left = None;
+        monitor.right = None;
+        monitor.up = None;
+        monitor.down = None;
+    }
+    monitor_proximity(monitors);
+}
+
+// xrandr moves everything back so the top left is at 0,0
+pub fn normalise_positions(monitors: &mut Monitors) {
+    let min_x = monitors.iter().filter(|m| m.is_enabled).map(|m| m.position.0).min().unwrap_or(0);
+    let min_y = monitors.iter().filter(|m| m.is_enabled).map(|m| m.position.1).min().unwrap_or(0);
+    for m in monitors.iter_mut().filter(|m| m.is_enabled) {
+        m.position.0 -= min_x;
+        m.position.1 -= min_y;
+    }
+}
+
+// the enabled monitor covering a pixel, if there is one
+pub fn monitor_at(monitors: &Monitors, pixel: (i32, i32)) -> Option<usize> {
+    monitors.iter().position(|m| {
+        m.is_enabled
+            && (m.position.0..m.position.0 + m.displayed_resolution.0).contains(&pixel.0)
+            && (m.position.1..m.position.1 + m.displayed_resolution.1).contains(&pixel.1)
+    })
+}
+
+// line a monitor up with the closest edges of the others, if they're within distance
+pub fn snap_to_edges(monitors: &mut Monitors, idx: usize, distance: (i32, i32)) {
+    let (x, y) = monitors[idx].position;
+    let (width, height) = monitors[idx].displayed_resolution;
+    let mut best_x: Option<i32> = None;
+    let mut best_y: Option<i32> = None;
+    let closer = |best: Option<i32>, current: i32, candidate: i32, distance: i32| {
+        let gap = (candidate - current).abs();
+        gap <= distance && best.is_none_or(|best| gap < (best - current).abs())
+    };
+
+    for (_, other) in monitors.iter().enumerate().filter(|(i, m)| *i != idx && m.is_enabled) {
+        // either of our edges on either of theirs
+        for edge in [other.position.0, other.position.0 + other.displayed_resolution.0] {
+            for candidate in [edge, edge - width] {
+                if closer(best_x, x, candidate, distance.0) { best_x = Some(candidate); }
+            }
+        }
+        for edge in [other.position.1, other.position.1 + other.displayed_resolution.1] {
+            for candidate in [edge, edge - height] {
+                if closer(best_y, y, candidate, distance.1) { best_y = Some(candidate); }
+            }
+        }
+    }
+    monitors[idx].position = (best_x.unwrap_or(x), best_y.unwrap_or(y));
+}
+
+// disable a monitor, closing the gap it leaves behind
+// - returns false without changing anything if it's the last enabled monitor
+pub fn disable_monitor(monitors: &mut Monitors, idx: usize) -> bool {
+    if !monitors.iter().enumerate().any(|(i, m)| i != idx && m.is_enabled) {
+        return false;
+    }
+    detach_monitor(monitors, idx);
+    monitors[idx].is_enabled = false;
+    monitors[idx].position = (-1,-1);
+    monitors[idx].resolution = (0,0);
+    true
+}
+
+// take a monitor out of the layout, moving whatever was connected after it back into the gap
+pub fn detach_monitor(monitors: &mut Monitors, idx: usize) {
+    // proximity ignores disabled monitors, so it won't find this one again while it's detached
+    let is_enabled = monitors[idx].is_enabled;
+    monitors[idx].is_enabled = false;
+
+    if let Some(right_idx) = monitors[idx].right {
+        monitors[right_idx].left = None;
+        let difference = monitors[right_idx].position.0 - monitors[idx].position.0;
+        shift_mons(monitors, right_idx, difference, false, Vec::new());
+    } else if let Some(down_idx) = monitors[idx].down {
+        let difference = monitors[down_idx].position.1 - monitors[idx].position.1;
+        monitors[down_idx].up = None;
+        shift_mons(monitors, down_idx, difference, true, Vec::new());
+    }
+
+    // purge this monitor from existence
+    if let Some(i) = monitors[idx].right { monitors[i].left = None }
+    if let Some(i) = monitors[idx].left { monitors[i].right = None }
+    if let Some(i) = monitors[idx].down { monitors[i].up = None }
+    if let Some(i) = monitors[idx].up { monitors[i].down = None }
+    monitors[idx].left = None;
+    monitors[idx].right = None;
+    monitors[idx].up = None;
+    monitors[idx].down = None;
+
+    monitor_proximity(monitors);
+
+    update_neighbor_positions(monitors);
+    monitors[idx].is_enabled = is_enabled;
+}
+
+// put a monitor next to another, pushing the monitors connected on that side out of the way
+// - align lines up the top/left (Start), middle or bottom/right (End) edges of the two monitors
+pub fn place_monitor(monitors: &mut Monitors, idx: usize, other_idx: usize, direction: Dir, align: Align) {
+    detach_monitor(monitors, idx);
+
+    let size = monitors[idx].displayed_resolution;
+    let (other_pos, other_size) = (monitors[other_idx].position, monitors[other_idx].displayed_resolution);
+
+    // make room, shift_mons moves everything connected further along too
+    match direction {
+        Dir::Right => if let Some(right_idx) = monitors[other_idx].right {
+            shift_mons(monitors, right_idx, -size.0, false, Vec::new());
+        },
+        Dir::Left => { shift_mons(monitors, other_idx, -size.0, false, Vec::new()); }
+        Dir::Down => if let Some(down_idx) = monitors[other_idx].down {
+            shift_mons(monitors, down_idx, -size.1, true, Vec::new());
+        },
+        Dir::Up => { shift_mons(monitors, other_idx, -size.1, true, Vec::new()); }
+    }
+
+    let offset = |other: i32, own: i32| match align {
+        Align::Start => 0,
+        Align::Center => (other - own) / 2,
+        Align::End => other - own,
+    };
+    monitors[idx].position = match direction {
+        Dir::Right => (other_pos.0 + other_size.0, other_pos.1 + offset(other_size.1, size.1)),
+        Dir::Left  => (other_pos.0, other_pos.1 + offset(other_size.1, size.1)),
+        Dir::Down  => (other_pos.0 + offset(other_size.0, size.0), other_pos.1 + other_size.1),
+        Dir::Up    => (other_pos.0 + offset(other_size.0, size.0), other_pos.1),
+    };
+
+    normalise_positions(monitors);
+    reset_proximity(monitors);
+}
+
+// enable a monitor at its best resolution, to the right of the row containing anchor_idx
+pub fn enable_monitor(monitors: &mut Monitors, idx: usize, anchor_idx: usize) {
+    monitors[idx].is_enabled = true;
+
+    //find rightmost monitor on first row, and connect it there
+    let right_idx = find_rightmost_monitor(monitors, anchor_idx);
+    let new_position = (
+        monitors[right_idx].position.0 + monitors[right_idx].resolution.0,
+        monitors[right_idx].position.1
+    );
+    monitors[idx].position = new_position;
+    let new_res = monitors[idx].sort_resolutions()[0];
+    monitors[idx].resolution = *new_res;
+    monitors[idx].scale = 1.0;
+    monitors[idx].update_scale();
+    monitor_proximity(monitors);
+}
+
+// enable a monitor where the attach point says, anchored on anchor_idx
+pub fn attach_monitor(monitors: &mut Monitors, idx: usize, anchor_idx: usize, attach: AttachPoint) {
+    enable_monitor(monitors, idx, anchor_idx);
+    if let AttachPoint::Beside(direction) = attach {
+        place_monitor(monitors, idx, anchor_idx, direction, Align::Start);
+    }
+}
+
+pub fn find_rightmost_monitor(monitors: &Monitors, idx: usize) -> usize {
+    if let Some(right_idx) = monitors[idx].right {
+        find_rightmost_monitor(monitors, right_idx)
+    } else {
+        idx
+    }
+}
+
 pub fn traverse_monitors(monitors: &mut Monitors, selected_idx: usize, direction: Dir) -> bool {
     let mut traverse: bool = false;
     match direction {
@@ -385,5 +574,5 @@ pub fn traverse_monitors(monitors: &mut Monitors, selected_idx: usize, direction
             }
         }
     }
-    return traverse;
+    traverse
 }
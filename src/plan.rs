@@ -0,0 +1,110 @@
+use crate::diff::*;
+use crate::monitor::*;
+use crate::xrandr::*;
+
+// one xrandr call in a staged apply
+#[derive(Debug, Clone, PartialEq)]
+pub struct Stage {
+    pub name: &'static str,
+    pub args: Vec<String>,
+}
+
+// the smallest screen that fits every enabled output
+fn extents<'a>(monitors: impl Iterator<Item = &'a Monitor>) -> (i32, i32) {
+    monitors
+        .filter(|m| m.is_enabled)
+        .fold((0, 0), |(w, h), m| (w.max(m.position.0 + m.displayed_resolution.0), h.max(m.position.1 + m.displayed_resolution.1)))
+}
+
+// split a change into calls xrandr is less likely to reject than one big one
+// - turn off outputs that are going away, freeing up their crtcs
+// - grow the framebuffer so every output fits at both its old and new size
+// - set the new modes, where the outputs are now
+// - move everything into place, letting xrandr shrink the framebuffer to fit
+// stages with nothing to do are left out
+pub fn plan_apply(before: &Monitors, after: &Monitors) -> Vec<Stage> {
+    let changed: Vec<&Monitor> = diff_layouts(before, after).iter()
+        .filter_map(|change| after.iter().find(|m| m.name == change.name))
+        .collect();
+    let mut stages: Vec<Stage> = Vec::new();
+
+    let mut off: Vec<String> = Vec::new();
+    for monitor in changed.iter().filter(|m| !m.is_enabled) {
+        off.extend(["--output".to_string(), monitor.name.clone(), "--off".to_string()]);
+    }
+    if !off.is_empty() {
+        stages.push(Stage { name: "turn off outputs", args: off });
+    }
+
+    // new sizes at the old positions have to fit as well as the final layout
+    let current = extents(before.iter());
+    let resized: Vec<Monitor> = changed.iter()
+        .filter(|m| m.is_enabled)
+        .map(|m| {
+            let mut resized = (*m).clone();
+            resized.position = before.iter().find(|b| b.name == m.name && b.is_enabled).map_or((0, 0), |b| b.position);
+            resized
+        })
+        .collect();
+    let needed = extents(after.iter().chain(resized.iter()));
+    let framebuffer = (current.0.max(needed.0), current.1.max(needed.1));
+    let fb_args = vec!["--fb".to_string(), format!("{}x{}", framebuffer.0, framebuffer.1)];
+    if framebuffer != current {
+        stages.push(Stage { name: "grow framebuffer", args: fb_args.clone() });
+    }
+
+    let mut modes: Vec<String> = if framebuffer != current { fb_args } else { Vec::new() };
+    let mut positions: Vec<String> = Vec::new();
+    for monitor in changed.iter().filter(|m| m.is_enabled) {
+        modes.extend([
+            "--output".to_string(), monitor.name.clone(),
+            "--mode".to_string(), format!("{}x{}", monitor.resolution.0, monitor.resolution.1),
+            "--rate".to_string(), monitor.framerate.to_string(),
+            "--scale".to_string(), format!("{:.2}", 1.0/monitor.scale),
+        ]);
+        positions.extend(["--output".to_string(), monitor.name.clone()]);
+        if monitor.is_primary { positions.push("--primary".to_string()); }
+        positions.extend(["--pos".to_string(), format!("{}x{}", monitor.position.0, monitor.position.1)]);
+    }
+    if diff_args(before, after).contains(&"--noprimary".to_string()) {
+        positions.push("--noprimary".to_string());
+    }
+    if modes.iter().any(|arg| arg == "--output") {
+        stages.push(Stage { name: "set modes", args: modes });
+    }
+    if !positions.is_empty() {
+        stages.push(Stage { name: "set positions", args: positions });
+    }
+    stages
+}
+
+// send the changes in one go, and if xrandr rejects that, again one stage at a time
+// - the result covers every call made, and says which stage failed if one did
+pub fn apply_planned(backend: &mut dyn Backend, before: &Monitors, after: &Monitors) -> ApplyResult {
+    let first = run_apply(backend, &diff_args(before, after));
+    if first.success {
+        return first;
+    }
+
+    let mut result = ApplyResult {
+        command: first.command.clone(),
+        success: true,
+        status: String::new(),
+        stdout: first.stdout.clone(),
+        stderr: first.stderr.clone(),
+    };
+    let stages = plan_apply(before, after);
+    for (number, stage) in stages.iter().enumerate() {
+        let step = run_apply(backend, &stage.args);
+        result.command.push_str(&format!("\n{}", step.command));
+        result.stdout.push_str(&step.stdout);
+        result.stderr.push_str(&step.stderr);
+        if !step.success {
+            result.success = false;
+            result.status = format!("stage {}/{} ({}) failed: {}", number + 1, stages.len(), stage.name, step.status);
+            return result;
+        }
+    }
+    result.status = format!("applied in {} stages after one call failed ({})", stages.len(), first.status);
+    result
+}
@@ -0,0 +1,124 @@
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::export::*;
+use crate::import::*;
+use crate::monitor::*;
+
+// files making up a saved profile
+const SETUP_FILE: &str = "setup";
+const LAYOUT_FILE: &str = "layout.sh";
+
+// a saved layout, along with the monitors it was saved for
+// - setup is a list of (connector, identity) pairs, so the layout can follow a panel to another port
+#[derive(Debug, Clone, PartialEq)]
+pub struct Profile {
+    pub name: String,
+    pub setup: Vec<(String, String)>,
+    pub layout: String,
+}
+
+// $XDG_CONFIG_HOME/monitor-tui, or ~/.config/monitor-tui
+pub fn config_dir() -> PathBuf {
+    match env::var_os("XDG_CONFIG_HOME") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir).join("monitor-tui"),
+        _ => PathBuf::from(env::var_os("HOME").unwrap_or_default()).join(".config").join("monitor-tui"),
+    }
+}
+
+pub fn profiles_dir() -> PathBuf {
+    config_dir().join("profiles")
+}
+
+// sorted identities of every connected monitor, enabled or not
+pub fn fingerprint(monitors: &Monitors) -> Vec<String> {
+    let mut identities: Vec<String> = monitors.iter().map(|m| m.identity()).collect();
+    identities.sort();
+    identities
+}
+
+impl Profile {
+    pub fn from_monitors(name: &str, monitors: &Monitors) -> Profile {
+        Profile {
+            name: name.to_string(),
+            setup: monitors.iter().map(|m| (m.name.clone(), m.identity())).collect(),
+            // disabled outputs are in the script as --off, so they stay off when it's applied
+            layout: to_shell_script(monitors),
+        }
+    }
+
+    pub fn fingerprint(&self) -> Vec<String> {
+        let mut identities: Vec<String> = self.setup.iter().map(|(_, identity)| identity.clone()).collect();
+        identities.sort();
+        identities
+    }
+
+    pub fn matches(&self, monitors: &Monitors) -> bool {
+        self.fingerprint() == fingerprint(monitors)
+    }
+
+    // apply the saved layout, renaming outputs to whichever port each panel is now on
+    pub fn apply_to(&self, monitors: &mut Monitors) -> io::Result<()> {
+        let mut renames: HashMap<String, String> = HashMap::new();
+        for (connector, identity) in &self.setup {
+            if let Some(monitor) = monitors.iter().find(|m| &m.identity() == identity) {
+                renames.insert(connector.clone(), monitor.name.clone());
+            }
+        }
+        let rename = |name: &String| renames.get(name).cloned().unwrap_or_else(|| name.clone());
+
+        let mut configs = parse_xrandr_command(&self.layout)?;
+        for config in configs.iter_mut() {
+            config.name = rename(&config.name);
+            if let Some((dir, other)) = &config.relative_to {
+                config.relative_to = Some((*dir, rename(other)));
+            }
+            config.same_as = config.same_as.as_ref().map(rename);
+        }
+        apply_output_configs(monitors, &configs)
+    }
+
+    pub fn save(&self, dir: &Path) -> io::Result<()> {
+        let profile_dir = dir.join(&self.name);
+        fs::create_dir_all(&profile_dir)?;
+        let setup: String = self.setup.iter().map(|(connector, identity)| format!("{} {}\n", connector, identity)).collect();
+        fs::write(profile_dir.join(SETUP_FILE), setup)?;
+        fs::write(profile_dir.join(LAYOUT_FILE), &self.layout)
+    }
+
+    pub fn load(dir: &Path, name: &str) -> io::Result<Profile> {
+        let profile_dir = dir.join(name);
+        let setup = fs::read_to_string(profile_dir.join(SETUP_FILE))?
+            .lines()
+            .filter_map(|line| line.split_once(' '))
+            .map(|(connector, identity)| (connector.to_string(), identity.to_string()))
+            .collect();
+        Ok(Profile {
+            name: name.to_string(),
+            setup,
+            layout: fs::read_to_string(profile_dir.join(LAYOUT_FILE))?,
+        })
+    }
+}
+
+// every profile saved in dir, sorted by name
+pub fn load_profiles(dir: &Path) -> io::Result<Vec<Profile>> {
+    let mut profiles: Vec<Profile> = Vec::new();
+    if !dir.exists() {
+        return Ok(profiles);
+    }
+    for entry in fs::read_dir(dir)?.flatten() {
+        if entry.path().is_dir() {
+            profiles.push(Profile::load(dir, &entry.file_name().to_string_lossy())?);
+        }
+    }
+    profiles.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(profiles)
+}
+
+pub fn find_matching_profile(dir: &Path, monitors: &Monitors) -> io::Result<Option<Profile>> {
+    Ok(load_profiles(dir)?.into_iter().find(|profile| profile.matches(monitors)))
+}
@@ -0,0 +1,215 @@
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::Path;
+use std::time::Instant;
+
+use crossterm::event::{KeyCode, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+use ratatui::layout::Rect;
+
+use crate::history::History;
+use crate::import::import_xrandr_command;
+use crate::keymap::{Key, Keymap};
+use crate::monitor::*;
+use crate::tui::{check_revert_timeout, handle_key, handle_mouse_event, start_session, wait_for_apply};
+use crate::xrandr::convert_monitors_to_args;
+use crate::{App, State};
+
+// a recorded session is a directory of plain text files, so it can be attached to a bug report
+// - query.txt    the `xrandr --query` output the session started from
+// - import.txt   the xrandr command the layout was imported from, if there was one
+// - keymap.txt   the keys in use, so a replay does the same thing with them
+// - keys.txt     every key pressed or mouse event, one per line
+// - layouts.txt  the layout after each key, as xrandr arguments
+// - commands.txt every xrandr command sent, one line per apply with any staged calls indented under it
+const QUERY_FILE: &str = "query.txt";
+const IMPORT_FILE: &str = "import.txt";
+const KEYMAP_FILE: &str = "keymap.txt";
+const KEYS_FILE: &str = "keys.txt";
+const LAYOUTS_FILE: &str = "layouts.txt";
+const COMMANDS_FILE: &str = "commands.txt";
+
+// where a replay records itself, to diff against the original
+const REPLAY_DIR: &str = "replay";
+
+// written in place of a key when an unconfirmed layout was reverted by the countdown
+pub const TIMEOUT_KEY: &str = "<timeout>";
+
+// name a key for keys.txt, printable characters are written as themselves
+// - keys the tui doesn't use aren't named, and aren't recorded
+pub fn key_name(key: KeyCode) -> Option<String> {
+    let name = match key {
+        KeyCode::Char(' ') => "<space>",
+        KeyCode::Char(c) => return Some(c.to_string()),
+        KeyCode::Enter => "<enter>",
+        KeyCode::Esc => "<esc>",
+        KeyCode::Tab => "<tab>",
+        KeyCode::Backspace => "<backspace>",
+        KeyCode::Up => "<up>",
+        KeyCode::Down => "<down>",
+        KeyCode::Left => "<left>",
+        KeyCode::Right => "<right>",
+        _ => return None,
+    };
+    Some(name.to_string())
+}
+
+pub fn parse_key(name: &str) -> Option<KeyCode> {
+    let mut chars = name.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => return Some(KeyCode::Char(c)),
+        (None, _) => return None,
+        _ => {}
+    }
+    match name {
+        "<space>" => Some(KeyCode::Char(' ')),
+        "<enter>" => Some(KeyCode::Enter),
+        "<esc>" => Some(KeyCode::Esc),
+        "<tab>" => Some(KeyCode::Tab),
+        "<backspace>" => Some(KeyCode::Backspace),
+        "<up>" => Some(KeyCode::Up),
+        "<down>" => Some(KeyCode::Down),
+        "<left>" => Some(KeyCode::Left),
+        "<right>" => Some(KeyCode::Right),
+        _ => None,
+    }
+}
+
+// mouse events are written along with the terminal size, which decides where monitors are drawn
+// e.g. <mouse down 40 12 120x40>
+// - only the left button moves monitors, so nothing else is recorded
+pub fn mouse_name(mouse: &MouseEvent, area: Rect) -> Option<String> {
+    let kind = match mouse.kind {
+        MouseEventKind::Down(MouseButton::Left) => "down",
+        MouseEventKind::Drag(MouseButton::Left) => "drag",
+        MouseEventKind::Up(MouseButton::Left) => "up",
+        _ => return None,
+    };
+    Some(format!("<mouse {} {} {} {}x{}>", kind, mouse.column, mouse.row, area.width, area.height))
+}
+
+pub fn parse_mouse(name: &str) -> Option<(MouseEvent, Rect)> {
+    let parts: Vec<&str> = name.strip_prefix("<mouse ")?.strip_suffix('>')?.split(' ').collect();
+    let [kind, column, row, size] = parts[..] else { return None };
+    let kind = match kind {
+        "down" => MouseEventKind::Down(MouseButton::Left),
+        "drag" => MouseEventKind::Drag(MouseButton::Left),
+        "up" => MouseEventKind::Up(MouseButton::Left),
+        _ => return None,
+    };
+    let (width, height) = size.split_once('x')?;
+    let mouse = MouseEvent { kind, column: column.parse().ok()?, row: row.parse().ok()?, modifiers: KeyModifiers::NONE };
+    Some((mouse, Rect::new(0, 0, width.parse().ok()?, height.parse().ok()?)))
+}
+
+// create the session directory, replacing anything recorded there before
+pub fn start_recording(dir: &Path, query: &str, import: Option<&str>, keymap: &Keymap) -> io::Result<()> {
+    fs::create_dir_all(dir)?;
+    fs::write(dir.join(QUERY_FILE), query)?;
+    fs::write(dir.join(KEYMAP_FILE), keymap.to_text())?;
+    match import {
+        Some(text) => fs::write(dir.join(IMPORT_FILE), text)?,
+        None => if dir.join(IMPORT_FILE).exists() { fs::remove_file(dir.join(IMPORT_FILE))? },
+    }
+    for file in [KEYS_FILE, LAYOUTS_FILE, COMMANDS_FILE] {
+        fs::write(dir.join(file), "")?;
+    }
+    Ok(())
+}
+
+fn append_line(path: &Path, line: &str) -> io::Result<()> {
+    let mut file = OpenOptions::new().append(true).create(true).open(path)?;
+    writeln!(file, "{}", line)
+}
+
+// written as we go, so a session that crashes is still recorded up to that point
+pub fn record_key(dir: &Path, key: &str, monitors: &Monitors) -> io::Result<()> {
+    append_line(&dir.join(KEYS_FILE), key)?;
+    append_line(&dir.join(LAYOUTS_FILE), &convert_monitors_to_args(monitors, false).join(" "))
+}
+
+// a staged apply made several calls, but a dry run replaying it only ever makes the first one
+pub fn record_command(dir: &Path, command: &str) -> io::Result<()> {
+    let mut lines = command.lines();
+    append_line(&dir.join(COMMANDS_FILE), lines.next().unwrap_or_default())?;
+    lines.try_for_each(|line| append_line(&dir.join(COMMANDS_FILE), &format!("  {}", line)))
+}
+
+fn read_lines(path: &Path) -> io::Result<Vec<String>> {
+    let text = fs::read_to_string(path)
+        .map_err(|err| io::Error::new(err.kind(), format!("can't read {}: {}", path.display(), err)))?;
+    Ok(text.lines().map(|line| line.to_string()).collect())
+}
+
+// the first call of each apply, leaving out the staged calls
+fn read_applies(path: &Path) -> io::Result<Vec<String>> {
+    Ok(read_lines(path)?.into_iter().filter(|line| !line.starts_with(' ')).collect())
+}
+
+// run a recorded session again without the tui, against the recorded query
+// - nothing is applied, and the replay is recorded into a replay directory next to the original
+// - fails at the first point the layout or the commands sent differ from the recording
+pub fn replay_session(dir: &Path) -> io::Result<String> {
+    let keys = read_lines(&dir.join(KEYS_FILE))?;
+    let layouts = read_lines(&dir.join(LAYOUTS_FILE))?;
+    let commands = read_applies(&dir.join(COMMANDS_FILE))?;
+    let import = fs::read_to_string(dir.join(IMPORT_FILE)).ok();
+    // recordings from before keymaps were saved used the default keys
+    let keymap = match fs::read_to_string(dir.join(KEYMAP_FILE)) {
+        Ok(text) => Keymap::parse(&text)
+            .map_err(|err| io::Error::new(err.kind(), format!("{}: {}", dir.join(KEYMAP_FILE).display(), err)))?,
+        Err(_) => Keymap::default(),
+    };
+
+    let replay_dir = dir.join(REPLAY_DIR);
+    let query = fs::read_to_string(dir.join(QUERY_FILE))
+        .map_err(|err| io::Error::new(err.kind(), format!("can't read {}: {}", dir.join(QUERY_FILE).display(), err)))?;
+    start_recording(&replay_dir, &query, import.as_deref(), &keymap)?;
+
+    let mut app = App::new(State::MonitorEdit, false);
+    app.keymap = keymap;
+    app.input = Some(replay_dir.join(QUERY_FILE));
+    app.dry_run = true;
+    app.record = Some(replay_dir.clone());
+
+    let mut monitors = app.backend().query()?;
+    if let Some(text) = &import {
+        import_xrandr_command(&mut monitors, text)?;
+    }
+    monitor_proximity(&mut monitors);
+    start_session(&monitors, &mut app);
+    let mut app_states = History::default();
+
+    for (step, key) in keys.iter().enumerate() {
+        if key == TIMEOUT_KEY {
+            app.revert_deadline = Some(Instant::now());
+            check_revert_timeout(&mut monitors, &mut app, &mut app_states);
+        } else if let Some((mouse, area)) = parse_mouse(key) {
+            handle_mouse_event(mouse, area, &mut monitors, &mut app, &mut app_states);
+        } else {
+            let code = Key::parse(key).ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, format!("unknown key '{}' in {}", key, KEYS_FILE)))?;
+            handle_key(code, &mut monitors, &mut app, &mut app_states);
+        }
+        wait_for_apply(&mut monitors, &mut app);
+        record_key(&replay_dir, key, &monitors)?;
+
+        let replayed = convert_monitors_to_args(&monitors, false).join(" ");
+        let recorded = layouts.get(step).map(|l| l.as_str()).unwrap_or("nothing, the recording ends here");
+        if replayed != recorded {
+            return Err(io::Error::other(format!(
+                "layout diverged at key {} ({}):\n  recorded: {}\n  replayed: {}",
+                step + 1, key, recorded, replayed
+            )));
+        }
+    }
+
+    let replayed = read_applies(&replay_dir.join(COMMANDS_FILE))?;
+    if let Some(idx) = (0..commands.len().max(replayed.len())).find(|&i| commands.get(i) != replayed.get(i)) {
+        let none = "nothing".to_string();
+        return Err(io::Error::other(format!(
+            "xrandr command {} diverged:\n  recorded: {}\n  replayed: {}",
+            idx + 1, commands.get(idx).unwrap_or(&none), replayed.get(idx).unwrap_or(&none)
+        )));
+    }
+
+    Ok(format!("Replayed {} keys and {} xrandr commands from {}, no divergence\n", keys.len(), commands.len(), dir.display()))
+}
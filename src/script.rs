@@ -0,0 +1,129 @@
+use std::io;
+
+use crate::debug::xrandr_debug::*;
+use crate::history::History;
+use crate::monitor::*;
+use crate::keymap::Key;
+use crate::tui::{handle_key, start_session, wait_for_apply};
+use crate::xrandr::parse_monitor_info;
+use crate::{App, State};
+
+// a script drives the tui without a terminal, one directive per line, run in order
+// - fixture: <name>              start over from a built in layout, default, 3_diff or 4_mons
+// - keys: <key> ...              keys as written in a recording, e.g. m l <enter>
+// - expect <output> <property>   check the layout so far, where property is one of
+//       pos X,Y | res WxH | rate R | scale S | primary | not-primary | enabled | disabled
+//       | left|right|up|down <output>|none
+// blank lines and lines starting with # are skipped
+// e.g.
+//     fixture: 3_diff
+//     keys: m l <enter>
+//     expect HDMI-1 pos 1920,0
+
+fn fixture(name: &str) -> Option<&'static str> {
+    match name {
+        "default" => Some(XRANDR_OUTPUT),
+        "3_diff" => Some(XRANDR_OUTPUT_3_DIFF),
+        "4_mons" => Some(XRANDR_OUTPUT_4_MONS),
+        _ => None,
+    }
+}
+
+// the session a script runs in, applying goes to the mock backend
+struct Session {
+    monitors: Monitors,
+    app: App,
+    app_states: History,
+}
+
+impl Session {
+    fn new(query: &str) -> Session {
+        let mut monitors = parse_monitor_info(query);
+        monitor_proximity(&mut monitors);
+        let mut app = App::new(State::MonitorEdit, true);
+        start_session(&monitors, &mut app);
+        Session { monitors, app, app_states: History::default() }
+    }
+}
+
+fn parse_pair(value: &str, separators: &[char]) -> Option<(i32, i32)> {
+    let (a, b) = value.split_once(separators)?;
+    Some((a.trim().parse().ok()?, b.trim().parse().ok()?))
+}
+
+// check one property of an output, returning what it actually was if it doesn't match
+fn check(monitors: &Monitors, name: &str, property: &str, value: Option<&str>) -> Result<(), String> {
+    let monitor = monitors.iter().find(|m| m.name == name).ok_or(format!("there's no output {}", name))?;
+    let needs_value = || format!("{} needs a value", property);
+    let close = |a: f32, b: &str| b.parse::<f32>().is_ok_and(|b| (a - b).abs() < 0.01);
+
+    let (matches, actual) = match property {
+        "pos" => {
+            let value = value.ok_or_else(needs_value)?;
+            (parse_pair(value, &[',', 'x']) == Some(monitor.position), format!("{},{}", monitor.position.0, monitor.position.1))
+        }
+        "res" => {
+            let value = value.ok_or_else(needs_value)?;
+            (parse_pair(value, &['x']) == Some(monitor.resolution), format!("{}x{}", monitor.resolution.0, monitor.resolution.1))
+        }
+        "rate" => (close(monitor.framerate, value.ok_or_else(needs_value)?), monitor.framerate.to_string()),
+        "scale" => (close(monitor.scale, value.ok_or_else(needs_value)?), format!("{:.2}", monitor.scale)),
+        "primary" | "not-primary" => (monitor.is_primary == (property == "primary"), if monitor.is_primary { "primary" } else { "not-primary" }.to_string()),
+        "enabled" | "disabled" => (monitor.is_enabled == (property == "enabled"), if monitor.is_enabled { "enabled" } else { "disabled" }.to_string()),
+        "left" | "right" | "up" | "down" => {
+            let value = value.ok_or_else(needs_value)?;
+            let neighbour = match property {
+                "left" => monitor.left,
+                "right" => monitor.right,
+                "up" => monitor.up,
+                _ => monitor.down,
+            };
+            let actual = neighbour.map(|i| monitors[i].name.clone()).unwrap_or("none".to_string());
+            (actual == value, actual)
+        }
+        _ => return Err(format!("unknown property '{}'", property)),
+    };
+    if matches { Ok(()) } else { Err(format!("got {}", actual)) }
+}
+
+// run a script, failing at the first directive that can't be run or expectation that isn't met
+pub fn run_script(text: &str) -> io::Result<String> {
+    let fail = |line: usize, message: String| io::Error::new(io::ErrorKind::InvalidData, format!("line {}: {}", line, message));
+    let mut session = Session::new(XRANDR_OUTPUT);
+    let mut keys = 0;
+    let mut expectations = 0;
+
+    for (idx, line) in text.lines().enumerate() {
+        let line_no = idx + 1;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        if let Some(name) = line.strip_prefix("fixture:") {
+            let query = fixture(name.trim()).ok_or_else(|| fail(line_no, format!("unknown fixture '{}'", name.trim())))?;
+            session = Session::new(query);
+        } else if let Some(names) = line.strip_prefix("keys:") {
+            for name in names.split_whitespace() {
+                // <Enter> reads better in a script, recordings are lowercase
+                let name = if name.starts_with('<') { name.to_lowercase() } else { name.to_string() };
+                let key = Key::parse(&name).ok_or_else(|| fail(line_no, format!("unknown key '{}'", name)))?;
+                handle_key(key, &mut session.monitors, &mut session.app, &mut session.app_states);
+                wait_for_apply(&mut session.monitors, &mut session.app);
+                keys += 1;
+            }
+        } else if let Some(expectation) = line.strip_prefix("expect ") {
+            let parts: Vec<&str> = expectation.split_whitespace().collect();
+            if parts.len() < 2 {
+                return Err(fail(line_no, format!("expect needs an output and a property, '{}'", line)));
+            }
+            check(&session.monitors, parts[0], parts[1], parts.get(2).copied())
+                .map_err(|err| fail(line_no, format!("expected {}, {}", expectation, err)))?;
+            expectations += 1;
+        } else {
+            return Err(fail(line_no, format!("can't understand '{}'", line)));
+        }
+    }
+
+    Ok(format!("Ran {} keys, {} expectations met\n", keys, expectations))
+}
@@ -0,0 +1,192 @@
+use crate::cli::*;
+use crate::monitor::*;
+use crate::profile::*;
+use crate::xrandr::*;
+use crate::tests::{temp_dir, MockBackend};
+
+fn debug_monitors() -> Monitors {
+    let mut monitors = get_monitor_info(true).unwrap();
+    monitor_proximity(&mut monitors);
+    monitors
+}
+
+fn args(text: &str) -> Vec<String> {
+    text.split_whitespace().map(|arg| arg.to_string()).collect()
+}
+
+#[test]
+fn list_shows_every_monitor() {
+    let mut monitors = debug_monitors();
+    monitors[2].is_enabled = false;
+    let list = list_monitors(&monitors);
+    let lines: Vec<&str> = list.lines().collect();
+    assert_eq!(lines[0], "HDMI-1 2560x1440+0+0 60hz scale 1.00 primary (DELL U2719D)");
+    assert_eq!(lines[1], "DP-1 1920x1080+2560+0 60hz scale 1.00 (LG FHD)");
+    assert_eq!(lines[2], "DP-2 disabled (LG FHD)");
+}
+
+#[test]
+fn export_formats() {
+    let monitors = debug_monitors();
+    assert!(export_monitors(&monitors, "script").unwrap().starts_with("#!/bin/sh"));
+    assert!(export_monitors(&monitors, "xorg").unwrap().contains("Section \"Monitor\""));
+    assert!(export_monitors(&monitors, "command").unwrap().starts_with("xrandr --output HDMI-1 --primary"));
+    assert_eq!(export_monitors(&monitors, "json").unwrap_err().kind(), std::io::ErrorKind::InvalidInput);
+}
+
+#[test]
+fn set_mode_moves_neighbours() {
+    let mut monitors = debug_monitors();
+    set_output(&mut monitors, "HDMI-1", &args("--mode 1920x1080 --rate 50")).unwrap();
+
+    assert_eq!(monitors[0].resolution, (1920, 1080));
+    assert_eq!(monitors[0].framerate, 50.0);
+    assert_eq!(monitors[1].position, (1920, 0));
+    assert_eq!(monitors[2].position, (3840, 0));
+    assert_eq!(monitors[0].right, Some(1));
+}
+
+#[test]
+fn set_primary_and_position() {
+    let mut monitors = debug_monitors();
+    set_output(&mut monitors, "DP-2", &args("--primary --pos 0x1440")).unwrap();
+
+    assert!(monitors[2].is_primary);
+    assert!(!monitors[0].is_primary);
+    assert_eq!(monitors[2].position, (0, 1440));
+    assert_eq!(monitors[0].down, Some(2));
+}
+
+#[test]
+fn set_negative_position() {
+    let mut monitors = debug_monitors();
+    set_output(&mut monitors, "DP-2", &args("--pos -1920x0")).unwrap();
+
+    // everything shifts so the layout starts at 0x0 again
+    assert_eq!(monitors[2].position, (0, 0));
+    assert_eq!(monitors[0].position, (1920, 0));
+    assert!(set_output(&mut monitors, "DP-2", &args("--primary --pos")).is_err());
+}
+
+#[test]
+fn set_off_closes_gap() {
+    let mut monitors = debug_monitors();
+    set_output(&mut monitors, "DP-1", &args("--off")).unwrap();
+
+    assert!(!monitors[1].is_enabled);
+    assert_eq!(monitors[2].position, (2560, 0));
+    assert!(convert_monitors_to_args(&monitors, false).ends_with(&args("--output DP-1 --off")));
+}
+
+#[test]
+fn set_rejects_bad_input() {
+    let mut monitors = debug_monitors();
+    assert!(set_output(&mut monitors, "DP-9", &args("--primary")).is_err());
+    assert!(set_output(&mut monitors, "DP-1", &args("--rotate left")).is_err());
+    assert!(set_output(&mut monitors, "DP-1", &args("--mode 1234x567")).is_err());
+    assert_eq!(monitors, debug_monitors());
+}
+
+#[test]
+fn set_command_applies_layout() {
+    let mut backend = MockBackend::new(debug_monitors());
+    run_command(&mut backend, &args("set DP-1 --mode 1280x720")).unwrap();
+
+    let applied = backend.applied[0].join(" ");
+    assert!(applied.contains("--output DP-1 --mode 1280x720"));
+    assert!(applied.contains("--output DP-2 --mode 1920x1080 --rate 60 --pos 3840x0"));
+}
+
+#[test]
+fn failed_apply_is_an_error() {
+    let mut backend = MockBackend::new(debug_monitors());
+    backend.exit_code = 1;
+    backend.stderr = "xrandr: cannot find mode\n".to_string();
+    let err = run_command(&mut backend, &args("set DP-1 --primary")).unwrap_err();
+    assert!(err.to_string().contains("xrandr: cannot find mode"));
+}
+
+#[test]
+fn apply_saved_profile() {
+    let dir = temp_dir("cli-apply");
+    let mut saved = debug_monitors();
+    set_output(&mut saved, "DP-2", &args("--pos 0x1440")).unwrap();
+    Profile::from_monitors("docked", &saved).save(&dir).unwrap();
+
+    let mut backend = MockBackend::new(debug_monitors());
+    apply_profile(&mut backend, &dir, "docked").unwrap();
+    assert!(backend.applied[0].join(" ").contains("--output DP-2 --mode 1920x1080 --rate 60 --pos 0x1440"));
+
+    assert!(apply_profile(&mut backend, &dir, "missing").is_err());
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn place_right_of_pushes_neighbours() {
+    let mut monitors = debug_monitors();
+    place_output(&mut monitors, "DP-2", &args("--right-of HDMI-1")).unwrap();
+
+    assert_eq!(monitors[2].position, (2560, 0));
+    assert_eq!(monitors[1].position, (4480, 0));
+    assert_eq!(monitors[0].right, Some(2));
+    assert_eq!(monitors[2].right, Some(1));
+}
+
+#[test]
+fn place_below_closes_gap() {
+    let mut monitors = debug_monitors();
+    place_output(&mut monitors, "DP-1", &args("--below HDMI-1 --align right")).unwrap();
+
+    assert_eq!(monitors[1].position, (640, 1440));
+    assert_eq!(monitors[2].position, (2560, 0));
+}
+
+#[test]
+fn place_above_moves_row_down() {
+    let mut monitors = debug_monitors();
+    place_output(&mut monitors, "DP-1", &args("--above HDMI-1 --align center")).unwrap();
+
+    assert_eq!(monitors[1].position, (320, 0));
+    assert_eq!(monitors[0].position, (0, 1080));
+    assert_eq!(monitors[2].position, (2560, 1080));
+}
+
+#[test]
+fn place_align_bottom() {
+    let mut monitors = debug_monitors();
+    place_output(&mut monitors, "HDMI-1", &args("--right-of DP-2 --align bottom")).unwrap();
+
+    assert_eq!(monitors[1].position, (0, 360));
+    assert_eq!(monitors[2].position, (1920, 360));
+    assert_eq!(monitors[0].position, (3840, 0));
+}
+
+#[test]
+fn place_rejects_bad_input() {
+    let mut monitors = debug_monitors();
+    assert!(place_output(&mut monitors, "DP-1", &args("--right-of DP-1")).is_err());
+    assert!(place_output(&mut monitors, "DP-1", &args("--right-of HDMI-1 --align left")).is_err());
+    assert!(place_output(&mut monitors, "DP-1", &args("--align top")).is_err());
+    assert!(place_output(&mut monitors, "DP-1", &args("--right-of DP-9")).is_err());
+    assert_eq!(monitors, debug_monitors());
+}
+
+#[test]
+fn list_as_json() {
+    let mut backend = MockBackend::new(debug_monitors());
+    let json: serde_json::Value = serde_json::from_str(&run_command(&mut backend, &args("list --json")).unwrap()).unwrap();
+    assert_eq!(json[2]["name"], "DP-2");
+    assert!(run_command(&mut backend, &args("list --yaml")).is_err());
+}
+
+#[test]
+fn commands_reject_extra_arguments() {
+    let mut backend = MockBackend::new(debug_monitors());
+    // a --json before the subcommand is passed on after it
+    let commands = ["list extra", "list --json extra", "list --json --json", "export --format xorg extra", "export --json", "apply docked extra"];
+    for command in commands {
+        let err = run_command(&mut backend, &args(command)).unwrap_err();
+        assert!(err.to_string().contains("usage:"), "{}", command);
+    }
+    assert!(backend.applied.is_empty());
+}
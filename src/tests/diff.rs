@@ -0,0 +1,57 @@
+use crate::diff::*;
+use crate::monitor::*;
+use crate::xrandr::*;
+
+fn args(text: &str) -> Vec<String> {
+    text.split_whitespace().map(|a| a.to_string()).collect()
+}
+
+fn debug_monitors() -> Monitors {
+    let mut monitors = get_monitor_info(true).unwrap();
+    monitor_proximity(&mut monitors);
+    monitors
+}
+
+#[test]
+fn unchanged_layout_sends_nothing() {
+    let monitors = debug_monitors();
+    assert!(diff_layouts(&monitors, &monitors).is_empty());
+    assert!(diff_args(&monitors, &monitors).is_empty());
+}
+
+#[test]
+fn only_changed_outputs_are_sent() {
+    let before = debug_monitors();
+    let mut after = before.clone();
+    after[2].position = (2560, 1080);
+
+    assert_eq!(diff_args(&before, &after), args("--output DP-2 --mode 1920x1080 --rate 60 --pos 2560x1080 --scale 1.00"));
+    assert_eq!(diff_layouts(&before, &after), vec![OutputDiff {
+        name: "DP-2".to_string(),
+        before: "1920x1080 60hz +4480+0 scale 1.00".to_string(),
+        after: "1920x1080 60hz +2560+1080 scale 1.00".to_string(),
+    }]);
+}
+
+#[test]
+fn disabled_outputs_are_turned_off() {
+    let before = debug_monitors();
+    let mut after = before.clone();
+    disable_monitor(&mut after, 1);
+
+    // DP-2 closes the gap, DP-1 goes off
+    assert_eq!(diff_args(&before, &after), args("--output DP-2 --mode 1920x1080 --rate 60 --pos 2560x0 --scale 1.00 --output DP-1 --off"));
+    assert_eq!(diff_layouts(&before, &after)[0].after, "off");
+    assert!(convert_monitors_to_args(&after, false).ends_with(&args("--output DP-1 --off")));
+}
+
+#[test]
+fn dropping_primary_unsets_it() {
+    let before = debug_monitors();
+    let mut after = before.clone();
+    after[0].is_primary = false;
+    assert_eq!(diff_args(&before, &after), args("--output HDMI-1 --mode 2560x1440 --rate 60 --pos 0x0 --scale 1.00 --noprimary"));
+
+    after[1].is_primary = true;
+    assert!(!diff_args(&before, &after).contains(&"--noprimary".to_string()));
+}
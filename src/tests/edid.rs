@@ -0,0 +1,79 @@
+use std::fs;
+
+use crate::edid::*;
+use crate::xrandr::*;
+use crate::debug::xrandr_debug::*;
+use crate::tests::temp_dir;
+
+#[test]
+fn decode_verbose_edids() {
+    let edids = parse_verbose_edids(XRANDR_VERBOSE_EDID);
+    assert_eq!(edids.len(), 3);
+
+    let edid = &edids["HDMI-1"];
+    assert_eq!(edid.manufacturer, "DEL");
+    assert_eq!(edid.product_code, 0xa0c4);
+    assert_eq!(edid.model, Some("DELL U2719D".to_string()));
+    assert_eq!(edid.serial_string, Some("7MT0186K0ABL".to_string()));
+    assert_eq!(edid.year, 2019);
+    assert_eq!(edid.fingerprint(), "DEL-a0c4-7MT0186K0ABL");
+}
+
+#[test]
+fn same_model_different_panels() {
+    let edids = parse_verbose_edids(XRANDR_VERBOSE_EDID);
+    assert_eq!(edids["DP-1"].model, edids["DP-2"].model);
+    assert_ne!(edids["DP-1"].fingerprint(), edids["DP-2"].fingerprint());
+}
+
+#[test]
+fn reject_invalid_edid() {
+    assert_eq!(parse_edid(&[0u8; 128]), None);
+    assert_eq!(parse_edid(&[0x00, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x00]), None);
+}
+
+#[test]
+fn monitors_carry_identity() {
+    let monitors = get_monitor_info(true).unwrap();
+    assert_eq!(monitors[0].display_name(), "DELL U2719D (HDMI-1)");
+    assert_eq!(monitors[1].identity(), "GSM-5b7f-005NTAB1C123");
+
+    let mut unknown = monitors[2].clone();
+    unknown.edid = None;
+    assert_eq!(unknown.display_name(), "DP-2");
+    assert_eq!(unknown.identity(), "DP-2");
+}
+
+#[test]
+fn connector_names() {
+    assert_eq!(normalise_connector("card0-HDMI-A-1"), "HDMI-1");
+    assert_eq!(normalise_connector("card1-DP-2"), "DP-2");
+    assert_eq!(normalise_connector("HDMI-1"), "HDMI-1");
+    assert_eq!(normalise_connector("HDMI-A-1"), "HDMI-1");
+}
+
+#[test]
+fn read_edids_from_sysfs() {
+    let root = temp_dir("edid");
+    let connector = root.join("card0-HDMI-A-1");
+    fs::create_dir_all(&connector).unwrap();
+    fs::create_dir_all(root.join("card0-DP-1")).unwrap();
+
+    // reuse the mock EDID from the verbose output as raw bytes
+    let hex: String = XRANDR_VERBOSE_EDID
+        .lines()
+        .skip_while(|line| line.trim() != "EDID:")
+        .skip(1)
+        .take(8)
+        .map(|line| line.trim())
+        .collect();
+    let bytes: Vec<u8> = (0..hex.len()).step_by(2).map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap()).collect();
+    fs::write(connector.join("edid"), bytes).unwrap();
+    fs::write(root.join("card0-DP-1").join("edid"), b"").unwrap();
+
+    let edids = read_sysfs_edids(&root);
+    fs::remove_dir_all(&root).unwrap();
+
+    assert_eq!(edids.len(), 1);
+    assert_eq!(edids["HDMI-1"].model, Some("DELL U2719D".to_string()));
+}
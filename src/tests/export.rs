@@ -0,0 +1,52 @@
+use crate::monitor::*;
+use crate::xrandr::*;
+use crate::export::*;
+
+#[test]
+fn shell_script_has_shebang_and_every_output() {
+    let mut monitors = get_monitor_info(true).unwrap();
+    monitor_proximity(&mut monitors);
+
+    let script = to_shell_script(&monitors);
+    let lines: Vec<&str> = script.lines().collect();
+    assert_eq!(lines[0], "#!/bin/sh");
+    assert_eq!(lines[2], "xrandr \\");
+    assert_eq!(lines[3], "    --output HDMI-1 --primary --mode 2560x1440 --rate 60 --pos 0x0 --scale 1.00 \\");
+    assert_eq!(lines[4], "    --output DP-1 --mode 1920x1080 --rate 60 --pos 2560x0 --scale 1.00 \\");
+    assert_eq!(lines[5], "    --output DP-2 --mode 1920x1080 --rate 60 --pos 4480x0 --scale 1.00");
+}
+
+#[test]
+fn shell_script_turns_off_disabled_outputs() {
+    let mut monitors = get_monitor_info(true).unwrap();
+    monitors[1].is_enabled = false;
+
+    let script = to_shell_script(&monitors);
+    assert!(script.contains("--output DP-1 --off\n"));
+    assert!(!script.contains("--output DP-1 --mode"));
+    assert!(script.contains("--output DP-2 --mode"));
+}
+
+#[test]
+fn xorg_conf_sections() {
+    let mut monitors = get_monitor_info(true).unwrap();
+    monitor_proximity(&mut monitors);
+
+    let conf = to_xorg_conf(&monitors);
+    assert_eq!(conf.matches("Section \"Monitor\"").count(), 3);
+    assert_eq!(conf.matches("EndSection").count(), 3);
+    assert!(conf.contains("    Identifier \"HDMI-1\"\n    Option \"Primary\" \"true\"\n    Option \"PreferredMode\" \"2560x1440\"\n    Option \"Position\" \"0 0\"\n"));
+    assert!(conf.contains("    Identifier \"DP-2\"\n    Option \"PreferredMode\" \"1920x1080\"\n    Option \"Position\" \"4480 0\"\n    Option \"RightOf\" \"DP-1\"\n"));
+}
+
+#[test]
+fn xorg_conf_vertical_and_disabled() {
+    let mut monitors = get_monitor_info(true).unwrap();
+    monitors[1].position = (0, 1440);
+    monitors[2].is_enabled = false;
+    monitor_proximity(&mut monitors);
+
+    let conf = to_xorg_conf(&monitors);
+    assert!(conf.contains("    Option \"Below\" \"HDMI-1\"\n"));
+    assert!(conf.contains("    Identifier \"DP-2\"\n    Option \"Enable\" \"false\"\nEndSection\n"));
+}
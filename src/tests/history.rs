@@ -0,0 +1,60 @@
+use crate::history::*;
+use crate::monitor::*;
+use crate::xrandr::*;
+
+// the debug monitors with the first one moved along by x
+fn layout(x: i32) -> Monitors {
+    let mut monitors = get_monitor_info(true).unwrap();
+    monitors[0].position.0 = x;
+    monitors
+}
+
+#[test]
+fn undo_then_redo() {
+    let mut history = History::default();
+    history.push("first", &layout(0));
+    history.push("second", &layout(1));
+
+    assert_eq!(history.undo(&layout(2)), Some(layout(1)));
+    assert_eq!(history.undo(&layout(1)), Some(layout(0)));
+    assert_eq!(history.undo(&layout(0)), None);
+
+    assert_eq!(history.redo(&layout(0)), Some(layout(1)));
+    assert_eq!(history.redo(&layout(1)), Some(layout(2)));
+    assert_eq!(history.redo(&layout(2)), None);
+}
+
+#[test]
+fn new_change_clears_redo() {
+    let mut history = History::default();
+    history.push("first", &layout(0));
+    history.undo(&layout(1));
+    history.push("other", &layout(0));
+    assert_eq!(history.redo(&layout(3)), None);
+}
+
+#[test]
+fn timeline_labels_follow_changes() {
+    let mut history = History::default();
+    history.push("first", &layout(0));
+    history.push("second", &layout(1));
+    history.undo(&layout(2));
+
+    let timeline = history.timeline(&layout(1));
+    let labels: Vec<&str> = timeline.iter().map(|entry| entry.label.as_str()).collect();
+    assert_eq!(labels, ["start", "first", "second"]);
+    assert_eq!(timeline[2].monitors, layout(2));
+    assert_eq!(history.position(), 1);
+}
+
+#[test]
+fn jump_backwards_and_forwards() {
+    let mut history = History::default();
+    history.push("first", &layout(0));
+    history.push("second", &layout(1));
+
+    assert_eq!(history.jump(&layout(2), 0), Some(layout(0)));
+    assert_eq!(history.position(), 0);
+    assert_eq!(history.jump(&layout(0), 2), Some(layout(2)));
+    assert_eq!(history.position(), 2);
+}
@@ -0,0 +1,131 @@
+use std::time::{Duration, Instant};
+
+use crossterm::event::KeyCode;
+
+use crate::history::History;
+use crate::hooks::*;
+use crate::monitor::*;
+use crate::tests::temp_dir;
+use crate::tui::*;
+use crate::xrandr::*;
+use crate::{App, State};
+
+#[test]
+fn parse_hooks_file() {
+    let hooks = Hooks::parse("# bar\npre  pkill polybar\n\npost polybar main &\npost nitrogen --restore\n").unwrap();
+    assert_eq!(hooks.pre, vec!["pkill polybar"]);
+    assert_eq!(hooks.post, vec!["polybar main &", "nitrogen --restore"]);
+
+    let err = Hooks::parse("pre true\nduring true\n").unwrap_err();
+    assert!(err.to_string().starts_with("line 2:"));
+
+    let dir = temp_dir("hooks-missing");
+    assert!(Hooks::load(&dir.join("hooks")).unwrap().is_empty());
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn hooks_get_profile_and_layout() {
+    let monitors = get_monitor_info(true).unwrap();
+    let log = HookLog::default();
+    let commands = vec![
+        "echo $MONITOR_TUI_HOOK $MONITOR_TUI_PROFILE".to_string(),
+        "printf '%s' \"$MONITOR_TUI_MONITORS\" | grep -c '\"name\"'".to_string(),
+        "echo oops >&2; exit 3".to_string(),
+    ];
+    run_hooks(&commands, "post", Some("docked"), &monitors, &log, &HookCancel::default());
+
+    let results = log.lock().unwrap().clone();
+    assert_eq!(results[0].output, "post docked\n");
+    assert_eq!(results[1].output, "3\n");
+    assert_eq!(results[2].output, "oops\n");
+    assert_eq!(results[2].status, "exit status: 3");
+}
+
+#[test]
+fn apply_runs_hooks_either_side_in_order() {
+    let dir = temp_dir("hooks-order");
+    let mut app = App::new(State::MonitorEdit, true);
+    // the post hook only says after if the slow pre hook had finished by then
+    let marker = dir.join("pre-done");
+    app.hooks = Hooks::parse(&format!(
+        "pre sleep 0.2; echo before; touch {0}\npost test -e {0} && echo after\n", marker.display()
+    )).unwrap();
+    let mut monitors = get_monitor_info(true).unwrap();
+    monitor_proximity(&mut monitors);
+    let mut app_states = History::default();
+
+    // swap, preview, apply
+    for key in [KeyCode::Char('m'), KeyCode::Char('l'), KeyCode::Enter, KeyCode::Char('s'), KeyCode::Char('s')] {
+        handle_key_press(key, &mut monitors, &mut app, &mut app_states);
+    }
+    // the tui carries on while the hooks run
+    assert_eq!(app.state, State::Applying);
+
+    wait_for_apply(&mut monitors, &mut app);
+    assert_eq!(app.state, State::ConfirmApply);
+    let results = app.hook_log.lock().unwrap().clone();
+    assert_eq!(results.len(), 2);
+    assert_eq!((results[0].stage.as_str(), results[0].output.as_str()), ("pre", "before\n"));
+    assert_eq!((results[1].stage.as_str(), results[1].output.as_str()), ("post", "after\n"));
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn hooks_do_not_wait_for_what_they_start_in_the_background() {
+    let monitors = get_monitor_info(true).unwrap();
+    let log = HookLog::default();
+    let commands = vec!["sleep 30 & echo started".to_string()];
+
+    let start = Instant::now();
+    run_hooks(&commands, "post", None, &monitors, &log, &HookCancel::default());
+    assert!(start.elapsed() < Duration::from_secs(5));
+
+    let results = log.lock().unwrap().clone();
+    assert_eq!(results[0].status, "exit status: 0");
+    assert_eq!(results[0].output, "started\n");
+}
+
+#[test]
+fn cancel_stops_the_hooks_but_not_the_apply() {
+    let mut app = App::new(State::MonitorEdit, true);
+    app.hooks = Hooks::parse("pre sleep 30\npre echo skipped\npost echo skipped\n").unwrap();
+    let mut monitors = get_monitor_info(true).unwrap();
+    monitor_proximity(&mut monitors);
+    let mut app_states = History::default();
+
+    for key in [KeyCode::Char('m'), KeyCode::Char('l'), KeyCode::Enter, KeyCode::Char('s'), KeyCode::Char('s')] {
+        handle_key_press(key, &mut monitors, &mut app, &mut app_states);
+    }
+    assert_eq!(app.state, State::Applying);
+
+    // other keys still wait, esc cancels the hook that's hanging
+    let start = Instant::now();
+    handle_key_press(KeyCode::Char('m'), &mut monitors, &mut app, &mut app_states);
+    assert_eq!(app.state, State::Applying);
+    handle_key_press(KeyCode::Esc, &mut monitors, &mut app, &mut app_states);
+    wait_for_apply(&mut monitors, &mut app);
+    assert!(start.elapsed() < Duration::from_secs(5));
+
+    assert_eq!(app.state, State::ConfirmApply);
+    let results = app.hook_log.lock().unwrap().clone();
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].status, "cancelled");
+}
+
+#[test]
+fn quit_while_applying() {
+    let mut app = App::new(State::MonitorEdit, true);
+    app.hooks = Hooks::parse("pre sleep 30\n").unwrap();
+    let mut monitors = get_monitor_info(true).unwrap();
+    monitor_proximity(&mut monitors);
+    let mut app_states = History::default();
+
+    for key in [KeyCode::Char('m'), KeyCode::Char('l'), KeyCode::Enter, KeyCode::Char('s'), KeyCode::Char('s')] {
+        handle_key_press(key, &mut monitors, &mut app, &mut app_states);
+    }
+    let start = Instant::now();
+    handle_key_press(KeyCode::Char('q'), &mut monitors, &mut app, &mut app_states);
+    assert!(start.elapsed() < Duration::from_secs(5));
+    assert_eq!(app.state, State::Quit);
+}
@@ -0,0 +1,142 @@
+use crate::Dir;
+use crate::monitor::*;
+use crate::xrandr::*;
+use crate::export::*;
+use crate::import::*;
+
+mod parse {
+    use super::*;
+
+    #[test]
+    fn single_command() {
+        let configs = parse_xrandr_command("xrandr --output HDMI-1 --primary --mode 1920x1080 --rate 59.94 --pos 0x0 --output DP-1 --off").unwrap();
+        assert_eq!(configs.len(), 2);
+        assert_eq!(configs[0].name, "HDMI-1");
+        assert!(configs[0].primary);
+        assert_eq!(configs[0].mode, Some((1920, 1080)));
+        assert_eq!(configs[0].rate, Some(59.94));
+        assert_eq!(configs[0].position, Some((0, 0)));
+        assert_eq!(configs[1].name, "DP-1");
+        assert!(configs[1].off);
+    }
+
+    #[test]
+    fn script_with_continuations_and_comments() {
+        let script = "#!/bin/sh\n# xrandr --output DP-2 --off\nsleep 1\n/usr/bin/xrandr \\\n    --output DP-1 --mode 1920x1080 \\\n    --output DP-2 --right-of DP-1 --scale 1.25x1.25 && polybar &\n";
+        let configs = parse_xrandr_command(script).unwrap();
+        assert_eq!(configs.len(), 2);
+        assert_eq!(configs[0].name, "DP-1");
+        assert_eq!(configs[1].name, "DP-2");
+        assert_eq!(configs[1].relative_to, Some((Dir::Right, "DP-1".to_string())));
+        assert_eq!(configs[1].scale, Some(1.25));
+    }
+
+    #[test]
+    fn ignored_options_skip_their_values() {
+        let configs = parse_xrandr_command("xrandr --dpi 96 --output DP-1 --rotate left --set audio off --pos 10x20").unwrap();
+        assert_eq!(configs.len(), 1);
+        assert_eq!(configs[0].position, Some((10, 20)));
+    }
+
+    #[test]
+    fn invalid_values_are_errors() {
+        assert!(parse_xrandr_command("xrandr --output DP-1 --pos left").is_err());
+        assert!(parse_xrandr_command("xrandr --output DP-1 --mode").is_err());
+    }
+}
+
+mod apply {
+    use super::*;
+
+    #[test]
+    fn absolute_positions() {
+        let mut monitors = get_monitor_info(true).unwrap();
+        monitor_proximity(&mut monitors);
+
+        import_xrandr_command(&mut monitors, "xrandr --output DP-1 --pos 0x0 --primary --output HDMI-1 --pos 1920x0 --output DP-2 --pos 0x1080").unwrap();
+        assert_eq!(monitors[0].position, (1920, 0));
+        assert_eq!(monitors[1].position, (0, 0));
+        assert_eq!(monitors[2].position, (0, 1080));
+        assert!(monitors[1].is_primary);
+        assert!(!monitors[0].is_primary);
+        assert_eq!(monitors[1].right, Some(0));
+        assert_eq!(monitors[1].down, Some(2));
+        assert_eq!(monitors[0].right, None);
+    }
+
+    #[test]
+    fn relative_positions() {
+        let mut monitors = get_monitor_info(true).unwrap();
+        monitor_proximity(&mut monitors);
+
+        import_xrandr_command(&mut monitors, "xrandr --output DP-1 --left-of HDMI-1 --output DP-2 --above HDMI-1").unwrap();
+        // DP-1 moves to -1920 and DP-2 to -1080, everything is shifted back to 0,0
+        assert_eq!(monitors[0].position, (1920, 1080));
+        assert_eq!(monitors[1].position, (0, 1080));
+        assert_eq!(monitors[2].position, (1920, 0));
+        assert_eq!(monitors[0].left, Some(1));
+        assert_eq!(monitors[0].up, Some(2));
+    }
+
+    #[test]
+    fn placement_relative_to_a_later_output() {
+        let mut monitors = get_monitor_info(true).unwrap();
+        monitor_proximity(&mut monitors);
+
+        // DP-2 is placed off DP-1 before DP-1 itself has moved
+        import_xrandr_command(&mut monitors, "xrandr --output DP-2 --right-of DP-1 --output DP-1 --below HDMI-1").unwrap();
+        assert_eq!(monitors[1].position, (0, 1440));
+        assert_eq!(monitors[2].position, (1920, 1440));
+        assert_eq!(monitors[2].left, Some(1));
+    }
+
+    #[test]
+    fn mode_rate_and_scale() {
+        let mut monitors = get_monitor_info(true).unwrap();
+
+        import_xrandr_command(&mut monitors, "xrandr --output HDMI-1 --mode 1920x1080 --rate 59.9 --scale 2").unwrap();
+        assert_eq!(monitors[0].resolution, (1920, 1080));
+        assert_eq!(monitors[0].framerate, 59.94);
+        assert_eq!(monitors[0].displayed_resolution, (3840, 2160));
+    }
+
+    #[test]
+    fn off_disables_output() {
+        let mut monitors = get_monitor_info(true).unwrap();
+
+        import_xrandr_command(&mut monitors, "xrandr --output DP-2 --off").unwrap();
+        assert!(!monitors[2].is_enabled);
+    }
+
+    #[test]
+    fn off_for_disconnected_output_is_ignored() {
+        let mut monitors = get_monitor_info(true).unwrap();
+
+        import_xrandr_command(&mut monitors, "xrandr --output HDMI-1 --auto --output HDMI-2 --off").unwrap();
+        assert!(monitors.iter().all(|m| m.is_enabled));
+    }
+
+    #[test]
+    fn unknown_output_or_mode_is_error() {
+        let mut monitors = get_monitor_info(true).unwrap();
+        assert!(import_xrandr_command(&mut monitors, "xrandr --output VGA-1 --auto").is_err());
+        assert!(import_xrandr_command(&mut monitors, "xrandr --output DP-1 --mode 3840x2160").is_err());
+        assert!(import_xrandr_command(&mut monitors, "echo nothing to see").is_err());
+    }
+
+    #[test]
+    fn exported_script_round_trips() {
+        let mut monitors = get_monitor_info(true).unwrap();
+        monitors[1].position = (0, 1440);
+        monitors[2].position = (2560, 0);
+        monitor_proximity(&mut monitors);
+
+        let mut imported = get_monitor_info(true).unwrap();
+        monitor_proximity(&mut imported);
+        import_xrandr_command(&mut imported, &to_shell_script(&monitors)).unwrap();
+        for (a, b) in monitors.iter().zip(imported.iter()) {
+            assert_eq!(a.position, b.position);
+            assert_eq!(a.resolution, b.resolution);
+        }
+    }
+}
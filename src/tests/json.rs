@@ -0,0 +1,51 @@
+use serde_json::Value;
+
+use crate::json::*;
+use crate::monitor::*;
+use crate::xrandr::*;
+
+fn debug_json(monitors: &Monitors) -> Value {
+    serde_json::from_str(&monitors_to_json(monitors)).unwrap()
+}
+
+#[test]
+fn covers_every_monitor() {
+    let mut monitors = get_monitor_info(true).unwrap();
+    monitor_proximity(&mut monitors);
+    let json = debug_json(&monitors);
+
+    assert_eq!(json.as_array().unwrap().len(), 3);
+    let hdmi = &json[0];
+    assert_eq!(hdmi["name"], "HDMI-1");
+    assert_eq!(hdmi["model"], "DELL U2719D");
+    assert_eq!(hdmi["identity"], "DEL-a0c4-7MT0186K0ABL");
+    assert_eq!(hdmi["primary"], true);
+    assert_eq!(hdmi["enabled"], true);
+    assert_eq!(hdmi["resolution"]["width"], 2560);
+    assert_eq!(hdmi["displayed_resolution"]["height"], 1440);
+    assert_eq!(hdmi["framerate"], 60.0);
+    assert_eq!(hdmi["scale"], 1.0);
+    assert_eq!(json[1]["position"]["x"], 2560);
+}
+
+#[test]
+fn neighbours_by_name() {
+    let mut monitors = get_monitor_info(true).unwrap();
+    monitor_proximity(&mut monitors);
+    let json = debug_json(&monitors);
+
+    assert_eq!(json[1]["neighbours"]["left"], "HDMI-1");
+    assert_eq!(json[1]["neighbours"]["right"], "DP-2");
+    assert_eq!(json[1]["neighbours"]["up"], Value::Null);
+}
+
+#[test]
+fn modes_largest_first() {
+    let monitors = get_monitor_info(true).unwrap();
+    let json = debug_json(&monitors);
+
+    let modes = json[0]["modes"].as_array().unwrap();
+    assert_eq!(modes.len(), monitors[0].available_resolutions.len());
+    assert_eq!(modes[0]["width"], 2560);
+    assert_eq!(modes[0]["rates"][1], 59.95);
+}
@@ -0,0 +1,98 @@
+use std::fs;
+
+use crossterm::event::{KeyCode, KeyModifiers};
+
+use crate::history::History;
+use crate::keymap::*;
+use crate::monitor::*;
+use crate::tests::temp_dir;
+use crate::tui::*;
+use crate::xrandr::*;
+use crate::{App, State};
+
+#[test]
+fn default_keys() {
+    let keymap = Keymap::default();
+    assert_eq!(keymap.action(Key::from(KeyCode::Char('h'))), Some(Action::Left));
+    assert_eq!(keymap.action(Key::from(KeyCode::Left)), Some(Action::Left));
+    assert_eq!(keymap.action(Key::from(KeyCode::Char('U'))), Some(Action::Redo));
+    assert_eq!(keymap.action(Key::from(KeyCode::Char('x'))), None);
+
+    // shift comes with capitals, it doesn't make them a different key
+    assert_eq!(keymap.action(Key::new(KeyCode::Char('D'), KeyModifiers::SHIFT)), Some(Action::Connections));
+    assert_eq!(keymap.action(Key::new(KeyCode::Char('h'), KeyModifiers::CONTROL)), None);
+}
+
+#[test]
+fn key_names_with_modifiers() {
+    let key = Key::parse("ctrl+alt+<left>").unwrap();
+    assert_eq!(key, Key::new(KeyCode::Left, KeyModifiers::CONTROL | KeyModifiers::ALT));
+    assert_eq!(key.name().unwrap(), "ctrl+alt+<left>");
+    assert_eq!(Key::parse("+"), Some(Key::from(KeyCode::Char('+'))));
+    assert_eq!(Key::parse("ctrl++"), Some(Key::new(KeyCode::Char('+'), KeyModifiers::CONTROL)));
+    assert_eq!(Key::parse("super+h"), None);
+}
+
+#[test]
+fn keymap_file_overrides_defaults() {
+    let keymap = Keymap::parse("# colemak\nleft  ctrl+h <left>\ndown  n <down>\n").unwrap();
+    assert_eq!(keymap.keys(Action::Left), [Key::parse("ctrl+h").unwrap(), Key::from(KeyCode::Left)]);
+    assert_eq!(keymap.action(Key::from(KeyCode::Char('h'))), None);
+    assert_eq!(keymap.action(Key::from(KeyCode::Char('n'))), Some(Action::Down));
+
+    // n was taken from no, which has nothing left
+    assert!(keymap.keys(Action::No).is_empty());
+    assert!(keymap.help().iter().all(|(_, description)| !description.starts_with("Cancel or revert")));
+    assert!(keymap.help().contains(&("ctrl+h <left>".to_string(), "Go left, or move the monitor left in monitor mode".to_string())));
+
+    // read back the way it's saved in a recording
+    assert_eq!(Keymap::parse(&keymap.to_text()).unwrap(), keymap);
+}
+
+#[test]
+fn bad_keymap_files() {
+    let err = Keymap::parse("left h\nsideways x\n").unwrap_err();
+    assert_eq!(err.to_string(), "line 2: unknown action 'sideways'");
+    let err = Keymap::parse("left ctrl+h\nright ctrl+h\n").unwrap_err();
+    assert_eq!(err.to_string(), "line 2: ctrl+h is already bound to left");
+    assert!(Keymap::parse("left\n").is_err());
+    assert!(Keymap::parse("left <f1>\n").is_err());
+}
+
+#[test]
+fn missing_keymap_file_is_default() {
+    let dir = temp_dir("keymap");
+    assert_eq!(Keymap::load(&dir.join("keymap")).unwrap(), Keymap::default());
+
+    fs::write(dir.join("keymap"), "quit ctrl+q\n").unwrap();
+    let keymap = Keymap::load(&dir.join("keymap")).unwrap();
+    assert_eq!(keymap.action(Key::parse("ctrl+q").unwrap()), Some(Action::Quit));
+}
+
+#[test]
+fn tui_follows_keymap() {
+    let mut app = App::new(State::MonitorEdit, true);
+    app.keymap = Keymap::parse("right ctrl+l\n").unwrap();
+    let mut monitors = get_monitor_info(true).unwrap();
+    monitor_proximity(&mut monitors);
+    let mut app_states = History::default();
+
+    handle_key(Key::from(KeyCode::Char('l')), &mut monitors, &mut app, &mut app_states);
+    assert_eq!(app.selected_idx, 0);
+    handle_key(Key::new(KeyCode::Char('l'), KeyModifiers::CONTROL), &mut monitors, &mut app, &mut app_states);
+    assert_eq!(app.selected_idx, 1);
+}
+
+#[test]
+fn hints_and_help_follow_the_keymap() {
+    let keep = [(&[Action::Select, Action::Yes][..], "Keep"), (&[Action::Back, Action::No][..], "Revert")];
+    assert_eq!(Keymap::default().hint(&keep), "<enter>/y: Keep    <esc>/n: Revert");
+
+    // n moves over to back, leaving no without a key
+    let keymap = Keymap::parse("yes ctrl+y\nback <esc> n\n").unwrap();
+    assert_eq!(keymap.hint(&keep), "<enter>/ctrl+y: Keep    <esc>/n: Revert");
+    assert_eq!(keymap.hint(&[(&[Action::No], "Revert")]), "");
+
+    let timeout = format!("reverting after {}s", crate::tui::REVERT_TIMEOUT.as_secs());
+    assert!(Keymap::default().help().iter().any(|(keys, description)| keys == "s" && description.contains(&timeout)));
+}
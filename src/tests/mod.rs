@@ -2,3 +2,76 @@
 mod monitors;
 #[cfg(test)]
 mod tui;
+#[cfg(test)]
+mod export;
+#[cfg(test)]
+mod import;
+#[cfg(test)]
+mod edid;
+#[cfg(test)]
+mod profile;
+#[cfg(test)]
+mod watch;
+#[cfg(test)]
+mod history;
+#[cfg(test)]
+mod cli;
+#[cfg(test)]
+mod json;
+#[cfg(test)]
+mod xrandr;
+#[cfg(test)]
+mod record;
+#[cfg(test)]
+mod script;
+#[cfg(test)]
+mod diff;
+#[cfg(test)]
+mod plan;
+#[cfg(test)]
+mod hooks;
+#[cfg(test)]
+mod keymap;
+
+// fresh scratch directory for tests touching the filesystem
+#[cfg(test)]
+pub fn temp_dir(name: &str) -> std::path::PathBuf {
+    let dir = std::env::temp_dir().join(format!("monitor-tui-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+// returns whatever monitors it's given and records what was applied
+#[cfg(test)]
+pub struct MockBackend {
+    pub monitors: crate::monitor::Monitors,
+    pub applied: Vec<Vec<String>>,
+    pub exit_code: i32,
+    pub stderr: String,
+}
+
+#[cfg(test)]
+impl MockBackend {
+    pub fn new(monitors: crate::monitor::Monitors) -> MockBackend {
+        MockBackend { monitors, applied: Vec::new(), exit_code: 0, stderr: String::new() }
+    }
+}
+
+#[cfg(test)]
+impl crate::xrandr::Backend for MockBackend {
+    fn query(&mut self) -> std::io::Result<crate::monitor::Monitors> {
+        Ok(self.monitors.clone())
+    }
+
+    fn apply(&mut self, args: &[String]) -> std::io::Result<std::process::Output> {
+        use std::os::unix::process::ExitStatusExt;
+        self.applied.push(args.to_vec());
+        Ok(std::process::Output {
+            // wait status, the exit code lives in the second byte
+            status: std::process::ExitStatus::from_raw(self.exit_code << 8),
+            stdout: Vec::new(),
+            stderr: self.stderr.clone().into_bytes(),
+        })
+    }
+}
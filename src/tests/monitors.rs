@@ -1,6 +1,7 @@
 use crate::*;
 use crate::monitor::*;
 use crate::xrandr::*;
+use crate::history::*;
 use crate::tui::*;
 use crossterm::event::KeyCode;
 
@@ -14,7 +15,7 @@ mod swap {
         fn swap_right() {
             let mut app = App::new(State::MonitorSwap, true);
             let mut monitors = get_monitor_info(true).unwrap();
-            let mut app_states: Vec<Monitors> = Vec::new();
+            let mut app_states = History::default();
             monitor_proximity(&mut monitors);
 
             handle_key_press(KeyCode::Char('l'), &mut monitors, &mut app, &mut app_states);
@@ -30,7 +31,7 @@ mod swap {
         fn swap_left() {
             let mut app = App::new(State::MonitorSwap, true);
             let mut monitors = get_monitor_info(true).unwrap();
-            let mut app_states: Vec<Monitors> = Vec::new();
+            let mut app_states = History::default();
             monitor_proximity(&mut monitors);
 
             app.current_idx = 1;
@@ -48,7 +49,7 @@ mod swap {
         fn swap_down() {
             let mut app = App::new(State::MonitorSwap, true);
             let mut monitors = get_monitor_info(true).unwrap();
-            let mut app_states: Vec<Monitors> = Vec::new();
+            let mut app_states = History::default();
 
             //veritcal stack monitors
             monitors[1].position = (0,1440);
@@ -68,7 +69,7 @@ mod swap {
         fn swap_up() {
             let mut app = App::new(State::MonitorSwap, true);
             let mut monitors = get_monitor_info(true).unwrap();
-            let mut app_states: Vec<Monitors> = Vec::new();
+            let mut app_states = History::default();
 
             //veritcal stack monitors
             monitors[1].position = (0,1440);
@@ -250,7 +251,7 @@ mod push {
         fn vert_push_up() {
             let mut app = App::new(State::MonitorSwap, true);
             let mut monitors = get_monitor_info(true).unwrap();
-            let mut app_states: Vec<Monitors> = Vec::new();
+            let mut app_states = History::default();
             monitor_proximity(&mut monitors);
 
             handle_key_press(KeyCode::Char('k'), &mut monitors, &mut app, &mut app_states);
@@ -266,7 +267,7 @@ mod push {
         fn vert_push_down() {
             let mut app = App::new(State::MonitorSwap, true);
             let mut monitors = get_monitor_info(true).unwrap();
-            let mut app_states: Vec<Monitors> = Vec::new();
+            let mut app_states = History::default();
             monitor_proximity(&mut monitors);
 
             handle_key_press(KeyCode::Char('j'), &mut monitors, &mut app, &mut app_states);
@@ -282,7 +283,7 @@ mod push {
         fn vert_push_up_from_middle() {
             let mut app = App::new(State::MonitorSwap, true);
             let mut monitors = get_monitor_info(true).unwrap();
-            let mut app_states: Vec<Monitors> = Vec::new();
+            let mut app_states = History::default();
             monitor_proximity(&mut monitors);
 
             app.current_idx = 1;
@@ -304,7 +305,7 @@ mod push {
         fn vert_push_down_from_middle() {
             let mut app = App::new(State::MonitorSwap, true);
             let mut monitors = get_monitor_info(true).unwrap();
-            let mut app_states: Vec<Monitors> = Vec::new();
+            let mut app_states = History::default();
             monitor_proximity(&mut monitors);
 
             app.current_idx = 1;
@@ -326,7 +327,7 @@ mod push {
         fn vert_push_with_below() {
             let mut app = App::new(State::MonitorSwap, true);
             let mut monitors = get_monitor_info(true).unwrap();
-            let mut app_states: Vec<Monitors> = Vec::new();
+            let mut app_states = History::default();
 
             monitors[0].position = (1920,0);
             monitors[1].position = (0,0);
@@ -349,7 +350,7 @@ mod push {
         fn vert_push_right_with_below() {
             let mut app = App::new(State::MonitorSwap, true);
             let mut monitors = get_monitor_info(true).unwrap();
-            let mut app_states: Vec<Monitors> = Vec::new();
+            let mut app_states = History::default();
 
             monitors[0].position = (0,0);
             monitors[1].position = (0,1440);
@@ -369,7 +370,7 @@ mod push {
         fn horizontal_push_right_with_above_and_left() {
             let mut app = App::new(State::MonitorSwap, true);
             let mut monitors = get_monitor_info(true).unwrap();
-            let mut app_states: Vec<Monitors> = Vec::new();
+            let mut app_states = History::default();
 
             monitors[0].position = (1920,1080);
             monitors[1].position = (0,1080);
@@ -389,7 +390,7 @@ mod push {
         fn vert_triangle_down_position() {
             let mut app = App::new(State::MonitorSwap, true);
             let mut monitors = get_monitor_info(true).unwrap();
-            let mut app_states: Vec<Monitors> = Vec::new();
+            let mut app_states = History::default();
             monitor_proximity(&mut monitors);
 
             // we expect this to look the same with the list in a different order.
@@ -412,7 +413,7 @@ mod push {
         fn vert_triangle_up_position() {
             let mut app = App::new(State::MonitorSwap, true);
             let mut monitors = get_monitor_info(true).unwrap();
-            let mut app_states: Vec<Monitors> = Vec::new();
+            let mut app_states = History::default();
             monitor_proximity(&mut monitors);
 
             // we expect this to look the same with the list in a different order.
@@ -443,7 +444,7 @@ mod push {
         fn vert_triangle_up_proximity() {
             let mut app = App::new(State::MonitorSwap, true);
             let mut monitors = get_monitor_info(true).unwrap();
-            let mut app_states: Vec<Monitors> = Vec::new();
+            let mut app_states = History::default();
             monitor_proximity(&mut monitors);
 
             // we expect this to look the same with the list in a different order.
@@ -472,7 +473,7 @@ mod push {
         fn vert_triangle_down_proximity() {
             let mut app = App::new(State::MonitorSwap, true);
             let mut monitors = get_monitor_info(true).unwrap();
-            let mut app_states: Vec<Monitors> = Vec::new();
+            let mut app_states = History::default();
             monitor_proximity(&mut monitors);
 
             // we expect this to look the same with the list in a different order.
@@ -500,7 +501,7 @@ mod push {
         fn horizontal_push_top_left_proximity(){
             let mut app = App::new(State::MonitorSwap, true);
             let mut monitors = get_monitor_info(true).unwrap();
-            let mut app_states: Vec<Monitors> = Vec::new();
+            let mut app_states = History::default();
 
             monitors[0].position = (0,0);
             monitors[1].position = (0,1440);
@@ -528,7 +529,7 @@ mod push {
         fn horizontal_push_top_right_proximity(){
             let mut app = App::new(State::MonitorSwap, true);
             let mut monitors = get_monitor_info(true).unwrap();
-            let mut app_states: Vec<Monitors> = Vec::new();
+            let mut app_states = History::default();
 
             monitors[0].position = (0,0);
             monitors[1].position = (0,1440);
@@ -556,7 +557,7 @@ mod push {
         fn horizontal_push_middle_left_proximity(){
             let mut app = App::new(State::MonitorSwap, true);
             let mut monitors = get_monitor_info(true).unwrap();
-            let mut app_states: Vec<Monitors> = Vec::new();
+            let mut app_states = History::default();
 
             monitors[0].position = (0,0);
             monitors[1].position = (0,1440);
@@ -586,7 +587,7 @@ mod push {
         fn horizontal_push_middle_right_proximity(){
             let mut app = App::new(State::MonitorSwap, true);
             let mut monitors = get_monitor_info(true).unwrap();
-            let mut app_states: Vec<Monitors> = Vec::new();
+            let mut app_states = History::default();
 
             monitors[0].position = (0,0);
             monitors[1].position = (0,1440);
@@ -616,7 +617,7 @@ mod push {
         fn horizontal_push_bottom_left_proximity(){
             let mut app = App::new(State::MonitorSwap, true);
             let mut monitors = get_monitor_info(true).unwrap();
-            let mut app_states: Vec<Monitors> = Vec::new();
+            let mut app_states = History::default();
 
             monitors[0].position = (0,0);
             monitors[1].position = (0,1440);
@@ -646,7 +647,7 @@ mod push {
         fn horizontal_push_bottom_right_proximity(){
             let mut app = App::new(State::MonitorSwap, true);
             let mut monitors = get_monitor_info(true).unwrap();
-            let mut app_states: Vec<Monitors> = Vec::new();
+            let mut app_states = History::default();
 
             monitors[0].position = (0,0);
             monitors[1].position = (0,1440);
@@ -673,3 +674,31 @@ mod push {
         }
     }
 }
+
+mod snap {
+    use super::*;
+
+    #[test]
+    fn monitor_under_pixel() {
+        let monitors = get_monitor_info(true).unwrap();
+        assert_eq!(monitor_at(&monitors, (0, 0)), Some(0));
+        assert_eq!(monitor_at(&monitors, (2560, 100)), Some(1));
+        assert_eq!(monitor_at(&monitors, (6000, 1079)), Some(2));
+        assert_eq!(monitor_at(&monitors, (6000, 1080)), None);
+    }
+
+    #[test]
+    fn snaps_to_closest_edges_in_range() {
+        let mut monitors = get_monitor_info(true).unwrap();
+
+        // just short of lining up under HDMI-1
+        monitors[2].position = (40, 1400);
+        snap_to_edges(&mut monitors, 2, (100, 100));
+        assert_eq!(monitors[2].position, (0, 1440));
+
+        // too far from anything to snap
+        monitors[2].position = (300, 1700);
+        snap_to_edges(&mut monitors, 2, (100, 100));
+        assert_eq!(monitors[2].position, (300, 1700));
+    }
+}
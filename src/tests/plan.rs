@@ -0,0 +1,91 @@
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+
+use crate::monitor::*;
+use crate::plan::*;
+use crate::xrandr::*;
+use crate::tests::temp_dir;
+use crate::{Align, Dir};
+
+fn args(text: &str) -> Vec<String> {
+    text.split_whitespace().map(|a| a.to_string()).collect()
+}
+
+// turn DP-1 off and put DP-2 under HDMI-1
+fn rearranged() -> (Monitors, Monitors) {
+    let mut before = get_monitor_info(true).unwrap();
+    monitor_proximity(&mut before);
+    let mut after = before.clone();
+    disable_monitor(&mut after, 1);
+    place_monitor(&mut after, 2, 0, Dir::Down, Align::Start);
+    (before, after)
+}
+
+// an xrandr that logs its arguments to calls.txt and fails when they match the pattern in fail-on
+fn fake_xrandr(dir: &Path, fail_on: &str) -> PathBuf {
+    let script = dir.join("xrandr");
+    fs::write(&script, "#!/bin/sh\n\
+        here=$(dirname \"$0\")\n\
+        echo \"$*\" >> \"$here/calls.txt\"\n\
+        pattern=$(cat \"$here/fail-on\")\n\
+        case \"$*\" in $pattern) echo \"xrandr: Configure crtc 0 failed\" >&2; exit 1;; esac\n").unwrap();
+    fs::set_permissions(&script, fs::Permissions::from_mode(0o755)).unwrap();
+    fs::write(dir.join("fail-on"), fail_on).unwrap();
+    script
+}
+
+// apply with the fake xrandr in place of the real one
+fn apply_with_fake(name: &str, fail_on: &str) -> (ApplyResult, Vec<String>) {
+    let dir = temp_dir(name);
+    let mut backend = XrandrBackend { program: fake_xrandr(&dir, fail_on) };
+    let (before, after) = rearranged();
+
+    let result = apply_planned(&mut backend, &before, &after);
+
+    let calls = fs::read_to_string(dir.join("calls.txt")).unwrap().lines().map(|l| l.to_string()).collect();
+    fs::remove_dir_all(&dir).unwrap();
+    (result, calls)
+}
+
+#[test]
+fn stages_in_order() {
+    let (before, after) = rearranged();
+    let stages = plan_apply(&before, &after);
+
+    assert_eq!(stages, vec![
+        Stage { name: "turn off outputs", args: args("--output DP-1 --off") },
+        Stage { name: "grow framebuffer", args: args("--fb 6400x2520") },
+        Stage { name: "set modes", args: args("--fb 6400x2520 --output DP-2 --mode 1920x1080 --rate 60 --scale 1.00") },
+        Stage { name: "set positions", args: args("--output DP-2 --pos 0x1440") },
+    ]);
+    assert!(plan_apply(&before, &before).is_empty());
+}
+
+#[test]
+fn one_call_when_it_works() {
+    let (result, calls) = apply_with_fake("plan-ok", "nothing");
+    assert!(result.success);
+    assert_eq!(calls.len(), 1);
+}
+
+#[test]
+fn retries_in_stages() {
+    // only the all in one call conflicts
+    let (result, calls) = apply_with_fake("plan-retry", "*--scale*--off*");
+    assert!(result.success, "{:?}", result);
+    assert_eq!(calls.len(), 5);
+    assert_eq!(calls[1], "--output DP-1 --off");
+    assert_eq!(calls[4], "--output DP-2 --pos 0x1440");
+    assert_eq!(result.command.lines().count(), 5);
+    assert!(result.status.starts_with("applied in 4 stages"));
+}
+
+#[test]
+fn reports_failed_stage() {
+    let (result, calls) = apply_with_fake("plan-fail", "*--mode*");
+    assert!(!result.success);
+    assert_eq!(calls.len(), 4);
+    assert!(result.status.starts_with("stage 3/4 (set modes) failed"));
+    assert!(result.stderr.contains("Configure crtc 0 failed"));
+}
@@ -0,0 +1,89 @@
+use std::fs;
+
+use crate::monitor::*;
+use crate::profile::*;
+use crate::xrandr::*;
+use crate::tests::temp_dir;
+
+fn stacked_monitors() -> Monitors {
+    let mut monitors = get_monitor_info(true).unwrap();
+    monitors[0].position = (1920, 0);
+    monitors[1].position = (0, 0);
+    monitors[2].position = (1920, 1440);
+    reset_proximity(&mut monitors);
+    monitors
+}
+
+#[test]
+fn save_and_load() {
+    let dir = temp_dir("profile-save");
+    let profile = Profile::from_monitors("docked", &stacked_monitors());
+    profile.save(&dir).unwrap();
+
+    assert_eq!(Profile::load(&dir, "docked").unwrap(), profile);
+    assert!(fs::read_to_string(dir.join("docked").join("setup")).unwrap().starts_with("HDMI-1 DEL-a0c4-7MT0186K0ABL\n"));
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn matches_same_panels_only() {
+    let monitors = get_monitor_info(true).unwrap();
+    let profile = Profile::from_monitors("docked", &stacked_monitors());
+    assert!(profile.matches(&monitors));
+
+    let mut fewer = monitors.clone();
+    fewer.pop();
+    assert!(!profile.matches(&fewer));
+}
+
+#[test]
+fn find_profile_in_dir() {
+    let dir = temp_dir("profile-find");
+    let mut laptop = get_monitor_info(true).unwrap();
+    laptop.truncate(1);
+    Profile::from_monitors("laptop", &laptop).save(&dir).unwrap();
+    Profile::from_monitors("docked", &stacked_monitors()).save(&dir).unwrap();
+
+    let monitors = get_monitor_info(true).unwrap();
+    let profiles = load_profiles(&dir).unwrap();
+    assert_eq!(profiles.len(), 2);
+    assert_eq!(profiles[0].name, "docked");
+    assert_eq!(find_matching_profile(&dir, &monitors).unwrap().unwrap().name, "docked");
+    assert_eq!(find_matching_profile(&dir, &laptop).unwrap().unwrap().name, "laptop");
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn layout_follows_panel_to_new_port() {
+    let profile = Profile::from_monitors("docked", &stacked_monitors());
+
+    // the two LG panels have been plugged into each other's ports
+    let mut monitors = get_monitor_info(true).unwrap();
+    let first = monitors[1].edid.clone();
+    monitors[1].edid = monitors[2].edid.clone();
+    monitors[2].edid = first;
+    monitor_proximity(&mut monitors);
+
+    profile.apply_to(&mut monitors).unwrap();
+    assert_eq!(monitors[0].position, (1920, 0));
+    // DP-2 now has the panel that was on DP-1 at 0,0
+    assert_eq!(monitors[2].position, (0, 0));
+    assert_eq!(monitors[1].position, (1920, 1440));
+}
+
+#[test]
+fn disabled_output_stays_off() {
+    let dir = temp_dir("profile-lid-closed");
+    let mut lid_closed = get_monitor_info(true).unwrap();
+    monitor_proximity(&mut lid_closed);
+    assert!(disable_monitor(&mut lid_closed, 0));
+    Profile::from_monitors("lid-closed", &lid_closed).save(&dir).unwrap();
+
+    let mut monitors = get_monitor_info(true).unwrap();
+    monitor_proximity(&mut monitors);
+    Profile::load(&dir, "lid-closed").unwrap().apply_to(&mut monitors).unwrap();
+    assert!(!monitors[0].is_enabled);
+    assert!(monitors[1].is_enabled && monitors[2].is_enabled);
+    assert_eq!(monitors[1].position, (0, 0));
+    fs::remove_dir_all(&dir).unwrap();
+}
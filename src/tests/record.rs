@@ -0,0 +1,143 @@
+use std::fs;
+
+use crossterm::event::KeyCode;
+
+use crate::debug::xrandr_debug::*;
+use crate::history::History;
+use crate::keymap::*;
+use crate::monitor::*;
+use crate::record::*;
+use crate::tests::temp_dir;
+use crate::tui::*;
+use crate::xrandr::*;
+use crate::{App, State};
+
+// drive the tui the way main_loop does while recording
+fn record_session(dir: &std::path::Path, keys: &[KeyCode]) {
+    record_session_with(dir, keys, Keymap::default());
+}
+
+fn record_session_with(dir: &std::path::Path, keys: &[KeyCode], keymap: Keymap) {
+    start_recording(dir, XRANDR_OUTPUT, None, &keymap).unwrap();
+    let mut app = App::new(State::MonitorEdit, false);
+    app.keymap = keymap;
+    app.input = Some(dir.join("query.txt"));
+    app.dry_run = true;
+    app.record = Some(dir.to_path_buf());
+
+    let mut monitors = app.backend().query().unwrap();
+    start_session(&monitors, &mut app);
+    let mut app_states = History::default();
+    for &key in keys {
+        handle_key_press(key, &mut monitors, &mut app, &mut app_states);
+        wait_for_apply(&mut monitors, &mut app);
+        record_key(dir, &key_name(key).unwrap(), &monitors).unwrap();
+    }
+}
+
+#[test]
+fn key_names_round_trip() {
+    for key in [KeyCode::Char('l'), KeyCode::Char('U'), KeyCode::Char(' '), KeyCode::Enter, KeyCode::Esc, KeyCode::Left] {
+        assert_eq!(parse_key(&key_name(key).unwrap()), Some(key));
+    }
+    assert_eq!(key_name(KeyCode::F(1)), None);
+    assert_eq!(parse_key("<nope>"), None);
+}
+
+#[test]
+fn replay_matches_recording() {
+    let dir = temp_dir("record-match");
+    // swap the first two monitors, then apply
+    record_session(&dir, &[KeyCode::Char('m'), KeyCode::Char('l'), KeyCode::Enter, KeyCode::Char('s'), KeyCode::Char('y')]);
+
+    let commands = fs::read_to_string(dir.join("commands.txt")).unwrap();
+    assert_eq!(commands.lines().count(), 1);
+    assert!(commands.contains("--output HDMI-1 --primary --mode 2560x1440 --rate 60 --pos 1920x0"));
+
+    let report = replay_session(&dir).unwrap();
+    assert!(report.starts_with("Replayed 5 keys and 1 xrandr commands"));
+    assert_eq!(fs::read_to_string(dir.join("replay/layouts.txt")).unwrap(), fs::read_to_string(dir.join("layouts.txt")).unwrap());
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn replay_matches_staged_apply() {
+    let dir = temp_dir("record-staged");
+    record_session(&dir, &[KeyCode::Char('m'), KeyCode::Char('l'), KeyCode::Enter, KeyCode::Char('s'), KeyCode::Char('y')]);
+
+    // pretend xrandr rejected the apply in one go, so it was sent again a stage at a time
+    let first = fs::read_to_string(dir.join("commands.txt")).unwrap();
+    fs::write(dir.join("commands.txt"), "").unwrap();
+    record_command(&dir, &format!("{}\nxrandr --output DP-1 --off\nxrandr --output HDMI-1 --pos 1920x0", first.trim())).unwrap();
+    let commands = fs::read_to_string(dir.join("commands.txt")).unwrap();
+    assert_eq!(commands.lines().nth(1), Some("  xrandr --output DP-1 --off"));
+
+    let report = replay_session(&dir).unwrap();
+    assert!(report.starts_with("Replayed 5 keys and 1 xrandr commands"));
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn recording_stops_when_it_cant_be_written() {
+    let dir = temp_dir("record-unwritable");
+    let mut app = App::new(State::MonitorEdit, true);
+    app.record = Some(dir.join("missing"));
+    let mut monitors = get_monitor_info(true).unwrap();
+    monitor_proximity(&mut monitors);
+    let mut app_states = History::default();
+
+    for key in [KeyCode::Char('m'), KeyCode::Char('l'), KeyCode::Enter, KeyCode::Char('s'), KeyCode::Char('s')] {
+        handle_key_press(key, &mut monitors, &mut app, &mut app_states);
+    }
+    wait_for_apply(&mut monitors, &mut app);
+
+    assert_eq!(app.record, None);
+    assert!(app.record_error.as_ref().unwrap().starts_with(&format!("Stopped recording to {}", dir.join("missing").display())));
+    assert_eq!(app.state, State::ConfirmApply);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn replay_reports_divergence() {
+    let dir = temp_dir("record-diverge");
+    record_session(&dir, &[KeyCode::Char('m'), KeyCode::Char('l'), KeyCode::Enter]);
+
+    // pretend the swap went differently when it was recorded
+    let layouts = fs::read_to_string(dir.join("layouts.txt")).unwrap();
+    let tampered: Vec<&str> = layouts.lines().enumerate()
+        .map(|(i, line)| if i == 1 { "--output HDMI-1 --off" } else { line })
+        .collect();
+    fs::write(dir.join("layouts.txt"), tampered.join("\n")).unwrap();
+
+    let err = replay_session(&dir).unwrap_err().to_string();
+    assert!(err.starts_with("layout diverged at key 2 (l):"));
+    assert!(err.contains("recorded: --output HDMI-1 --off"));
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn mouse_names_round_trip() {
+    use crossterm::event::{KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+    use ratatui::layout::Rect;
+
+    let mouse = MouseEvent { kind: MouseEventKind::Drag(MouseButton::Left), column: 40, row: 12, modifiers: KeyModifiers::NONE };
+    let area = Rect::new(0, 0, 120, 40);
+    let name = mouse_name(&mouse, area).unwrap();
+    assert_eq!(name, "<mouse drag 40 12 120x40>");
+    assert_eq!(parse_mouse(&name), Some((mouse, area)));
+
+    let scroll = MouseEvent { kind: MouseEventKind::ScrollUp, ..mouse };
+    assert_eq!(mouse_name(&scroll, area), None);
+}
+
+#[test]
+fn replay_uses_recorded_keymap() {
+    let dir = temp_dir("record-keymap");
+    // m, then n to move right instead of l
+    let keymap = Keymap::parse("right n <right>").unwrap();
+    record_session_with(&dir, &[KeyCode::Char('m'), KeyCode::Char('n'), KeyCode::Enter], keymap);
+
+    let layouts = fs::read_to_string(dir.join("layouts.txt")).unwrap();
+    assert!(layouts.lines().last().unwrap().contains("--output HDMI-1 --primary --mode 2560x1440 --rate 60 --pos 1920x0"));
+    assert!(replay_session(&dir).is_ok());
+}
@@ -0,0 +1,32 @@
+use std::fs;
+use std::path::Path;
+
+use crate::script::*;
+
+// every script in tests/scripts is a regression case
+#[test]
+fn scripts_pass() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("src/tests/scripts");
+    for entry in fs::read_dir(dir).unwrap() {
+        let path = entry.unwrap().path();
+        let text = fs::read_to_string(&path).unwrap();
+        if let Err(err) = run_script(&text) {
+            panic!("{}: {}", path.display(), err);
+        }
+    }
+}
+
+#[test]
+fn failed_expectation_reports_line() {
+    let script = "fixture: 3_diff\n\nkeys: l\nexpect DP-2 pos 0,0\n";
+    let err = run_script(script).unwrap_err().to_string();
+    assert_eq!(err, "line 4: expected DP-2 pos 0,0, got 4480,0");
+}
+
+#[test]
+fn bad_directives_are_rejected() {
+    assert_eq!(run_script("keys: <nope>").unwrap_err().to_string(), "line 1: unknown key '<nope>'");
+    assert_eq!(run_script("fixture: 9_mons").unwrap_err().to_string(), "line 1: unknown fixture '9_mons'");
+    assert!(run_script("expect HDMI-1 shape round").unwrap_err().to_string().contains("unknown property 'shape'"));
+    assert!(run_script("press l").unwrap_err().to_string().contains("can't understand"));
+}
@@ -2,8 +2,39 @@ use crossterm::event::KeyCode;
 use crate::*;
 use crate::monitor::*;
 use crate::xrandr::*;
+use crate::history::*;
 use crate::tui::*;
 
+// a monitor's menu open on an entry, the mock monitors are in a row so l reaches any of them
+fn menu_at(monitor: usize, entry: MenuEntry) -> (App, Monitors, History) {
+    let mut app = App::new(State::MonitorEdit, true);
+    let mut monitors = get_monitor_info(true).unwrap();
+    monitor_proximity(&mut monitors);
+    let mut app_states = History::default();
+    for _ in 0..monitor {
+        handle_key_press(KeyCode::Char('l'), &mut monitors, &mut app, &mut app_states);
+    }
+    handle_key_press(KeyCode::Enter, &mut monitors, &mut app, &mut app_states);
+    while app.menu_entry != entry {
+        handle_key_press(KeyCode::Down, &mut monitors, &mut app, &mut app_states);
+    }
+    (app, monitors, app_states)
+}
+
+// swap the first two monitors in monitor mode
+fn swap_first_two(monitors: &mut Monitors, app: &mut App, app_states: &mut History) {
+    handle_key_press(KeyCode::Char('m'), monitors, app, app_states);
+    handle_key_press(KeyCode::Char('l'), monitors, app, app_states);
+    handle_key_press(KeyCode::Enter, monitors, app, app_states);
+}
+
+// open the preview and apply from it, waiting for the apply to finish
+fn preview_and_apply(monitors: &mut Monitors, app: &mut App, app_states: &mut History) {
+    handle_key_press(KeyCode::Char('s'), monitors, app, app_states);
+    handle_key_press(KeyCode::Char('s'), monitors, app, app_states);
+    wait_for_apply(monitors, app);
+}
+
 // test menu navigation
 mod menu {
     use super::*;
@@ -11,7 +42,7 @@ mod menu {
     fn test_update_menu_left() {
         let mut app = App::new(State::MonitorEdit, true);
         let mut monitors = get_monitor_info(true).unwrap();
-        let mut app_states: Vec<Monitors> = Vec::new();
+        let mut app_states = History::default();
         monitor_proximity(&mut monitors);
 
         assert_eq!(app.current_idx, 0);
@@ -26,7 +57,7 @@ mod menu {
     fn test_update_menu_right() {
         let mut app = App::new(State::MonitorEdit, true);
         let mut monitors = get_monitor_info(true).unwrap();
-        let mut app_states: Vec<Monitors> = Vec::new();
+        let mut app_states = History::default();
         monitor_proximity(&mut monitors);
 
         handle_key_press(KeyCode::Char('l'), &mut monitors, &mut app, &mut app_states);
@@ -39,7 +70,7 @@ mod menu {
     fn select_monitor_sets_menu_select_state() {
         let mut app = App::new(State::MonitorEdit, true);
         let mut monitors = get_monitor_info(true).unwrap();
-        let mut app_states: Vec<Monitors> = Vec::new();
+        let mut app_states = History::default();
         handle_key_press(KeyCode::Enter, &mut monitors, &mut app, &mut app_states);
         assert_eq!(app.state, State::MenuSelect);
     }
@@ -48,7 +79,7 @@ mod menu {
     fn navigate_monitor_menu_to_resolution() {
         let mut app = App::new(State::MonitorEdit, true);
         let mut monitors = get_monitor_info(true).unwrap();
-        let mut app_states: Vec<Monitors> = Vec::new();
+        let mut app_states = History::default();
         handle_key_press(KeyCode::Enter, &mut monitors, &mut app, &mut app_states);
         // navigate to resolutions
         handle_key_press(KeyCode::Char('j'), &mut monitors, &mut app, &mut app_states);
@@ -59,7 +90,7 @@ mod menu {
     fn menu_shoudnt_underflow() {
         let mut app = App::new(State::MenuSelect, true);
         let mut monitors = get_monitor_info(true).unwrap();
-        let mut app_states: Vec<Monitors> = Vec::new();
+        let mut app_states = History::default();
         monitor_proximity(&mut monitors);
 
         assert_eq!(app.menu_entry, MenuEntry::Position);
@@ -73,7 +104,7 @@ mod menu {
     fn menu_shouldnt_overflow() {
         let mut app = App::new(State::MenuSelect, true);
         let mut monitors = get_monitor_info(true).unwrap();
-        let mut app_states: Vec<Monitors> = Vec::new();
+        let mut app_states = History::default();
         monitor_proximity(&mut monitors);
 
         assert_eq!(app.menu_entry, MenuEntry::Position);
@@ -89,7 +120,7 @@ mod menu {
     fn test_full_menu_navigation() {
         let mut app = App::new(State::MenuSelect, true);
         let mut monitors = get_monitor_info(true).unwrap();
-        let mut app_states: Vec<Monitors> = Vec::new();
+        let mut app_states = History::default();
         monitor_proximity(&mut monitors);
 
         assert_eq!(app.menu_entry, MenuEntry::Position);
@@ -104,6 +135,8 @@ mod menu {
         handle_key_press(KeyCode::Char('j'), &mut monitors, &mut app, &mut app_states);
         assert_eq!(app.menu_entry, MenuEntry::Primary);
         handle_key_press(KeyCode::Char('j'), &mut monitors, &mut app, &mut app_states);
+        assert_eq!(app.menu_entry, MenuEntry::Enabled);
+        handle_key_press(KeyCode::Char('j'), &mut monitors, &mut app, &mut app_states);
         assert_eq!(app.menu_entry, MenuEntry::Left);
         handle_key_press(KeyCode::Char('j'), &mut monitors, &mut app, &mut app_states);
         assert_eq!(app.menu_entry, MenuEntry::Down);
@@ -139,7 +172,7 @@ mod state {
     fn m_key_sets_monitor_swap_state() {
         let mut app = App::new(State::MonitorEdit, true);
         let mut monitors = get_monitor_info(true).unwrap();
-        let mut app_states: Vec<Monitors> = Vec::new();
+        let mut app_states = History::default();
         monitor_proximity(&mut monitors);
         handle_key_press(KeyCode::Char('m'), &mut monitors, &mut app, &mut app_states);
         assert_eq!(app.state, State::MonitorSwap);
@@ -149,7 +182,7 @@ mod state {
     fn enter_in_monitor_swap_returns_to_monitor_edit_state() {
         let mut app = App::new(State::MonitorEdit, true);
         let mut monitors = get_monitor_info(true).unwrap();
-        let mut app_states: Vec<Monitors> = Vec::new();
+        let mut app_states = History::default();
         handle_key_press(KeyCode::Char('m'), &mut monitors, &mut app, &mut app_states);
         handle_key_press(KeyCode::Enter, &mut monitors, &mut app, &mut app_states);
         assert_eq!(app.state, State::MonitorEdit);
@@ -159,7 +192,7 @@ mod state {
     fn navigate_to_info_edit_state() {
         let mut app = App::new(State::MonitorEdit, true);
         let mut monitors = get_monitor_info(true).unwrap();
-        let mut app_states: Vec<Monitors> = Vec::new();
+        let mut app_states = History::default();
         handle_key_press(KeyCode::Enter, &mut monitors, &mut app, &mut app_states);
         // navigate to resolutions
         handle_key_press(KeyCode::Char('j'), &mut monitors, &mut app, &mut app_states);
@@ -170,7 +203,7 @@ mod state {
     fn debug_popup() {
         let mut app = App::new(State::MonitorEdit, true);
         let mut monitors = get_monitor_info(true).unwrap();
-        let mut app_states: Vec<Monitors> = Vec::new();
+        let mut app_states = History::default();
         handle_key_press(KeyCode::Char('d'), &mut monitors, &mut app, &mut app_states);
         assert_eq!(app.state, State::DebugPopup);
     }
@@ -179,7 +212,7 @@ mod state {
     fn esc_from_debug() {
         let mut app = App::new(State::MonitorEdit, true);
         let mut monitors = get_monitor_info(true).unwrap();
-        let mut app_states: Vec<Monitors> = Vec::new();
+        let mut app_states = History::default();
         app.update_state(State::InfoEdit);
         handle_key_press(KeyCode::Char('d'), &mut monitors, &mut app, &mut app_states);
         handle_key_press(KeyCode::Esc, &mut monitors, &mut app, &mut app_states);
@@ -190,7 +223,7 @@ mod state {
     fn esc_from_info_edit() {
         let mut app = App::new(State::MonitorEdit, true);
         let mut monitors = get_monitor_info(true).unwrap();
-        let mut app_states: Vec<Monitors> = Vec::new();
+        let mut app_states = History::default();
         app.update_state(State::InfoEdit);
         handle_key_press(KeyCode::Esc, &mut monitors, &mut app, &mut app_states);
         assert_eq!(app.state, State::MenuSelect);
@@ -200,7 +233,7 @@ mod state {
     fn debug_on_qmark() {
         let mut app = App::new(State::MonitorEdit, true);
         let mut monitors = get_monitor_info(true).unwrap();
-        let mut app_states: Vec<Monitors> = Vec::new();
+        let mut app_states = History::default();
         handle_key_press(KeyCode::Char('?'), &mut monitors, &mut app, &mut app_states);
         assert_eq!(app.state, State::HelpPopup);
     }
@@ -209,8 +242,744 @@ mod state {
     fn quit_on_q() {
         let mut app = App::new(State::MonitorEdit, true);
         let mut monitors = get_monitor_info(true).unwrap();
-        let mut app_states: Vec<Monitors> = Vec::new();
+        let mut app_states = History::default();
         handle_key_press(KeyCode::Char('q'), &mut monitors, &mut app, &mut app_states);
         assert_eq!(app.state, State::Quit);
     }
 }
+
+mod apply {
+    use super::*;
+    use std::time::Instant;
+
+    // a fresh session with the first two swapped, which records the starting layout in the history
+    fn swapped_layout() -> (App, Monitors, History) {
+        let mut app = App::new(State::MonitorEdit, true);
+        let mut monitors = get_monitor_info(true).unwrap();
+        monitor_proximity(&mut monitors);
+        let mut app_states = History::default();
+
+        swap_first_two(&mut monitors, &mut app, &mut app_states);
+        (app, monitors, app_states)
+    }
+
+    #[test]
+    fn apply_asks_for_confirmation() {
+        let (mut app, mut monitors, mut app_states) = swapped_layout();
+        preview_and_apply(&mut monitors, &mut app, &mut app_states);
+        assert_eq!(app.state, State::ConfirmApply);
+        assert!(app.revert_deadline.is_some());
+        assert_eq!(app.revert_state.as_ref().unwrap()[0].name, "HDMI-1");
+    }
+
+    #[test]
+    fn confirm_keeps_layout() {
+        let (mut app, mut monitors, mut app_states) = swapped_layout();
+        preview_and_apply(&mut monitors, &mut app, &mut app_states);
+        handle_key_press(KeyCode::Enter, &mut monitors, &mut app, &mut app_states);
+        assert_eq!(app.state, State::MonitorEdit);
+        assert_eq!(monitors[0].name, "DP-1");
+        assert_eq!(app.applied_state, Some(monitors.clone()));
+        assert_eq!(app.revert_state, None);
+    }
+
+    #[test]
+    fn esc_reverts_layout() {
+        let (mut app, mut monitors, mut app_states) = swapped_layout();
+        preview_and_apply(&mut monitors, &mut app, &mut app_states);
+        handle_key_press(KeyCode::Esc, &mut monitors, &mut app, &mut app_states);
+        wait_for_apply(&mut monitors, &mut app);
+        assert_eq!(app.state, State::MonitorEdit);
+        assert_eq!(monitors[0].name, "HDMI-1");
+        assert_eq!(monitors[0].position, (0, 0));
+    }
+
+    #[test]
+    fn timeout_reverts_to_last_confirmed() {
+        let (mut app, mut monitors, mut app_states) = swapped_layout();
+        preview_and_apply(&mut monitors, &mut app, &mut app_states);
+        handle_key_press(KeyCode::Char('y'), &mut monitors, &mut app, &mut app_states);
+
+        // move the swapped monitor back and apply again, but let the timer run out
+        handle_key_press(KeyCode::Char('m'), &mut monitors, &mut app, &mut app_states);
+        handle_key_press(KeyCode::Char('h'), &mut monitors, &mut app, &mut app_states);
+        handle_key_press(KeyCode::Enter, &mut monitors, &mut app, &mut app_states);
+        preview_and_apply(&mut monitors, &mut app, &mut app_states);
+
+        check_revert_timeout(&mut monitors, &mut app, &mut app_states);
+        assert_eq!(app.state, State::ConfirmApply);
+
+        app.revert_deadline = Some(Instant::now());
+        check_revert_timeout(&mut monitors, &mut app, &mut app_states);
+        wait_for_apply(&mut monitors, &mut app);
+        assert_eq!(app.state, State::MonitorEdit);
+        assert_eq!(monitors[0].name, "DP-1");
+    }
+}
+
+mod apply_result {
+    use super::*;
+    use crate::tests::MockBackend;
+
+    #[test]
+    fn apply_shows_command_result() {
+        let mut app = App::new(State::MonitorEdit, true);
+        let mut monitors = get_monitor_info(true).unwrap();
+        let mut app_states = History::default();
+        preview_and_apply(&mut monitors, &mut app, &mut app_states);
+
+        let result = app.apply_result.clone().unwrap();
+        assert!(result.success);
+        assert!(result.command.starts_with("xrandr --output HDMI-1 --primary --mode 2560x1440"));
+    }
+
+    #[test]
+    fn preview_without_changes_applies_nothing() {
+        let mut app = App::new(State::MonitorEdit, true);
+        let mut monitors = get_monitor_info(true).unwrap();
+        let mut app_states = History::default();
+        app.startup_state = Some(monitors.clone());
+        handle_key_press(KeyCode::Char('s'), &mut monitors, &mut app, &mut app_states);
+        assert_eq!(app.state, State::ApplyPreview);
+
+        handle_key_press(KeyCode::Enter, &mut monitors, &mut app, &mut app_states);
+        assert_eq!(app.state, State::MonitorEdit);
+        assert_eq!(app.apply_result, None);
+    }
+
+    #[test]
+    fn print_json_quits_instead_of_applying() {
+        let mut app = App::new(State::MonitorEdit, true);
+        app.print_json = true;
+        let mut monitors = get_monitor_info(true).unwrap();
+        let mut app_states = History::default();
+        preview_and_apply(&mut monitors, &mut app, &mut app_states);
+
+        assert_eq!(app.state, State::Quit);
+        assert_eq!(app.apply_result, None);
+    }
+
+    #[test]
+    fn dry_run_shows_command() {
+        let mut app = App::new(State::MonitorEdit, true);
+        app.dry_run = true;
+        let mut monitors = get_monitor_info(true).unwrap();
+        let mut app_states = History::default();
+        preview_and_apply(&mut monitors, &mut app, &mut app_states);
+
+        assert_eq!(app.state, State::ConfirmApply);
+        assert_eq!(app.revert_deadline, None);
+        assert!(app.apply_result.unwrap().stdout.starts_with("dry run, not applied: xrandr --output HDMI-1"));
+    }
+
+    #[test]
+    fn failure_captures_stderr() {
+        let mut backend = MockBackend::new(get_monitor_info(true).unwrap());
+        backend.exit_code = 1;
+        backend.stderr = "xrandr: Configure crtc 2 failed\n".to_string();
+
+        let result = run_apply(&mut backend, &["--output".to_string(), "DP-1".to_string()]);
+        assert!(!result.success);
+        assert_eq!(result.command, "xrandr --output DP-1");
+        assert_eq!(result.status, "exit status: 1");
+        assert_eq!(result.stderr, "xrandr: Configure crtc 2 failed\n");
+    }
+
+    #[test]
+    fn scroll_and_close_result() {
+        let mut app = App::new(State::MonitorEdit, true);
+        let mut monitors = get_monitor_info(true).unwrap();
+        let mut app_states = History::default();
+        app.update_state(State::ApplyResult);
+
+        handle_key_press(KeyCode::Char('j'), &mut monitors, &mut app, &mut app_states);
+        handle_key_press(KeyCode::Char('j'), &mut monitors, &mut app, &mut app_states);
+        handle_key_press(KeyCode::Char('k'), &mut monitors, &mut app, &mut app_states);
+        assert_eq!(app.result_scroll, 1);
+
+        handle_key_press(KeyCode::Esc, &mut monitors, &mut app, &mut app_states);
+        assert_eq!(app.state, State::MonitorEdit);
+    }
+}
+
+mod reset {
+    use super::*;
+
+    #[test]
+    fn reset_to_startup() {
+        let mut app = App::new(State::MonitorEdit, true);
+        let mut monitors = get_monitor_info(true).unwrap();
+        monitor_proximity(&mut monitors);
+        let mut app_states = History::default();
+        swap_first_two(&mut monitors, &mut app, &mut app_states);
+        assert_eq!(monitors[0].name, "DP-1");
+
+        // nothing applied yet, so startup is the default choice
+        handle_key_press(KeyCode::Char('r'), &mut monitors, &mut app, &mut app_states);
+        assert_eq!(app.state, State::ResetPopup);
+        assert_eq!(app.reset_target, ResetTarget::Startup);
+        handle_key_press(KeyCode::Enter, &mut monitors, &mut app, &mut app_states);
+
+        assert_eq!(app.state, State::MonitorEdit);
+        assert_eq!(monitors[0].name, "HDMI-1");
+        assert_eq!(monitors[0].position, (0, 0));
+
+        // and the reset itself can be undone
+        handle_key_press(KeyCode::Char('u'), &mut monitors, &mut app, &mut app_states);
+        assert_eq!(monitors[0].name, "DP-1");
+    }
+
+    #[test]
+    fn reset_to_last_applied() {
+        let mut app = App::new(State::MonitorEdit, true);
+        let mut monitors = get_monitor_info(true).unwrap();
+        monitor_proximity(&mut monitors);
+        let mut app_states = History::default();
+        app.startup_state = Some(monitors.clone());
+
+        swap_first_two(&mut monitors, &mut app, &mut app_states);
+        preview_and_apply(&mut monitors, &mut app, &mut app_states);
+        handle_key_press(KeyCode::Enter, &mut monitors, &mut app, &mut app_states);
+
+        // swap back, then reset to what was applied
+        handle_key_press(KeyCode::Char('m'), &mut monitors, &mut app, &mut app_states);
+        handle_key_press(KeyCode::Char('h'), &mut monitors, &mut app, &mut app_states);
+        handle_key_press(KeyCode::Enter, &mut monitors, &mut app, &mut app_states);
+        assert_eq!(monitors[0].name, "HDMI-1");
+        handle_key_press(KeyCode::Char('r'), &mut monitors, &mut app, &mut app_states);
+        assert_eq!(app.reset_target, ResetTarget::LastApplied);
+        handle_key_press(KeyCode::Enter, &mut monitors, &mut app, &mut app_states);
+        assert_eq!(monitors[0].name, "DP-1");
+
+        // or all the way back to startup
+        handle_key_press(KeyCode::Char('r'), &mut monitors, &mut app, &mut app_states);
+        handle_key_press(KeyCode::Char('j'), &mut monitors, &mut app, &mut app_states);
+        handle_key_press(KeyCode::Enter, &mut monitors, &mut app, &mut app_states);
+        assert_eq!(monitors[0].name, "HDMI-1");
+    }
+
+    #[test]
+    fn esc_cancels_reset() {
+        let mut app = App::new(State::MonitorEdit, true);
+        let mut monitors = get_monitor_info(true).unwrap();
+        monitor_proximity(&mut monitors);
+        let mut app_states = History::default();
+        swap_first_two(&mut monitors, &mut app, &mut app_states);
+
+        handle_key_press(KeyCode::Char('r'), &mut monitors, &mut app, &mut app_states);
+        handle_key_press(KeyCode::Esc, &mut monitors, &mut app, &mut app_states);
+        assert_eq!(app.state, State::MonitorEdit);
+        assert_eq!(monitors[0].name, "DP-1");
+    }
+}
+
+mod history {
+    use super::*;
+
+    #[test]
+    fn move_is_a_single_labelled_entry() {
+        let mut app = App::new(State::MonitorEdit, true);
+        let mut monitors = get_monitor_info(true).unwrap();
+        monitor_proximity(&mut monitors);
+        let mut app_states = History::default();
+        swap_first_two(&mut monitors, &mut app, &mut app_states);
+
+        assert_eq!(app_states.undo.len(), 1);
+        assert_eq!(app_states.undo[0].label, "swap HDMI-1↔DP-1");
+    }
+
+    #[test]
+    fn redo_after_undo() {
+        let mut app = App::new(State::MonitorEdit, true);
+        let mut monitors = get_monitor_info(true).unwrap();
+        monitor_proximity(&mut monitors);
+        let mut app_states = History::default();
+        swap_first_two(&mut monitors, &mut app, &mut app_states);
+        let swapped = monitors.clone();
+
+        handle_key_press(KeyCode::Char('u'), &mut monitors, &mut app, &mut app_states);
+        assert_eq!(monitors[0].name, "HDMI-1");
+        handle_key_press(KeyCode::Char('U'), &mut monitors, &mut app, &mut app_states);
+        assert_eq!(monitors, swapped);
+    }
+
+    #[test]
+    fn resolution_change_is_labelled() {
+        let mut app = App::new(State::MonitorEdit, true);
+        let mut monitors = get_monitor_info(true).unwrap();
+        monitor_proximity(&mut monitors);
+        let mut app_states = History::default();
+
+        // select the second resolution of the first monitor
+        handle_key_press(KeyCode::Enter, &mut monitors, &mut app, &mut app_states);
+        handle_key_press(KeyCode::Char('j'), &mut monitors, &mut app, &mut app_states);
+        handle_key_press(KeyCode::Enter, &mut monitors, &mut app, &mut app_states);
+        handle_key_press(KeyCode::Char('j'), &mut monitors, &mut app, &mut app_states);
+        handle_key_press(KeyCode::Enter, &mut monitors, &mut app, &mut app_states);
+
+        let res = monitors[0].resolution;
+        assert_eq!(app_states.undo[0].label, format!("resolution {}x{} on HDMI-1", res.0, res.1));
+    }
+
+    #[test]
+    fn jump_from_history_popup() {
+        let mut app = App::new(State::MonitorEdit, true);
+        let mut monitors = get_monitor_info(true).unwrap();
+        monitor_proximity(&mut monitors);
+        let start = monitors.clone();
+        let mut app_states = History::default();
+        swap_first_two(&mut monitors, &mut app, &mut app_states);
+        handle_key_press(KeyCode::Char('p'), &mut monitors, &mut app, &mut app_states);
+
+        handle_key_press(KeyCode::Char('H'), &mut monitors, &mut app, &mut app_states);
+        assert_eq!(app.state, State::HistoryPopup);
+        assert_eq!(app.history_idx, 2);
+        handle_key_press(KeyCode::Char('k'), &mut monitors, &mut app, &mut app_states);
+        handle_key_press(KeyCode::Char('k'), &mut monitors, &mut app, &mut app_states);
+        handle_key_press(KeyCode::Char('k'), &mut monitors, &mut app, &mut app_states);
+        assert_eq!(app.history_idx, 0);
+        handle_key_press(KeyCode::Enter, &mut monitors, &mut app, &mut app_states);
+
+        assert_eq!(app.state, State::MonitorEdit);
+        assert_eq!(monitors, start);
+        // both changes can still be redone
+        assert_eq!(app_states.redo.len(), 2);
+    }
+}
+
+mod mouse {
+    use super::*;
+    use crossterm::event::{KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+    use ratatui::layout::Rect;
+
+    const TERMINAL: Rect = Rect { x: 0, y: 0, width: 100, height: 40 };
+
+    // the terminal cell that maps back closest to a pixel
+    fn cell_for(monitors: &Monitors, app: &App, pixel: (i32, i32)) -> (u16, u16) {
+        let transform = CanvasTransform::new(monitor_area(TERMINAL), monitors, app);
+        let area = transform.area;
+        let mut cells = (area.x..area.x + area.width).flat_map(|c| (area.y..area.y + area.height).map(move |r| (c, r)));
+        let distance = |cell: &(u16, u16)| {
+            let p = transform.to_pixel(cell.0, cell.1);
+            (p.0 - pixel.0).abs() + (p.1 - pixel.1).abs()
+        };
+        let first = cells.next().unwrap();
+        cells.fold(first, |best, cell| if distance(&cell) < distance(&best) { cell } else { best })
+    }
+
+    fn mouse(kind: MouseEventKind, cell: (u16, u16), monitors: &mut Monitors, app: &mut App, app_states: &mut History) {
+        let event = MouseEvent { kind, column: cell.0, row: cell.1, modifiers: KeyModifiers::NONE };
+        handle_mouse_event(event, TERMINAL, monitors, app, app_states);
+    }
+
+    #[test]
+    fn click_selects_monitor() {
+        let mut app = App::new(State::MonitorEdit, true);
+        let mut monitors = get_monitor_info(true).unwrap();
+        monitor_proximity(&mut monitors);
+        let mut app_states = History::default();
+        let start = monitors.clone();
+
+        let cell = cell_for(&monitors, &app, (5440, 540));
+        mouse(MouseEventKind::Down(MouseButton::Left), cell, &mut monitors, &mut app, &mut app_states);
+        mouse(MouseEventKind::Up(MouseButton::Left), cell, &mut monitors, &mut app, &mut app_states);
+
+        assert_eq!(app.selected_idx, 2);
+        assert_eq!(monitors, start);
+        assert!(app_states.undo.is_empty());
+    }
+
+    #[test]
+    fn drag_snaps_to_edges() {
+        let mut app = App::new(State::MonitorEdit, true);
+        let mut monitors = get_monitor_info(true).unwrap();
+        monitor_proximity(&mut monitors);
+        let mut app_states = History::default();
+
+        // pick DP-2 up by the middle and drop it roughly under HDMI-1
+        let from = cell_for(&monitors, &app, (5440, 540));
+        mouse(MouseEventKind::Down(MouseButton::Left), from, &mut monitors, &mut app, &mut app_states);
+        let to = cell_for(&monitors, &app, (1000, 2000));
+        mouse(MouseEventKind::Drag(MouseButton::Left), to, &mut monitors, &mut app, &mut app_states);
+        assert!(app.drag.is_some());
+        mouse(MouseEventKind::Up(MouseButton::Left), to, &mut monitors, &mut app, &mut app_states);
+
+        assert!(app.drag.is_none());
+        assert_eq!(monitors[2].position, (0, 1440));
+        assert_eq!(monitors[0].down, Some(2));
+        assert_eq!(app_states.undo.last().unwrap().label, "drag DP-2");
+    }
+
+    #[test]
+    fn click_outside_canvas_does_nothing() {
+        let mut app = App::new(State::MonitorEdit, true);
+        let mut monitors = get_monitor_info(true).unwrap();
+        monitor_proximity(&mut monitors);
+        let mut app_states = History::default();
+        let start = monitors.clone();
+
+        // zoomed in on DP-1, the border just right of the canvas lines up with DP-2
+        app.view = Some((2560, 0, 4480, 1080));
+        let area = monitor_area(TERMINAL);
+        let cell = (area.x + area.width, area.y + area.height / 2);
+        let pixel = CanvasTransform::new(area, &monitors, &app).to_pixel(cell.0, cell.1);
+        assert_eq!(monitor_at(&monitors, pixel), Some(2));
+
+        mouse(MouseEventKind::Down(MouseButton::Left), cell, &mut monitors, &mut app, &mut app_states);
+        mouse(MouseEventKind::Drag(MouseButton::Left), (area.x + 1, area.y + 1), &mut monitors, &mut app, &mut app_states);
+        mouse(MouseEventKind::Up(MouseButton::Left), (area.x + 1, area.y + 1), &mut monitors, &mut app, &mut app_states);
+
+        assert!(app.drag.is_none());
+        assert_eq!(app.selected_idx, 0);
+        assert_eq!(monitors, start);
+        assert!(app_states.undo.is_empty());
+    }
+
+    #[test]
+    fn mouse_only_works_in_monitor_edit() {
+        let mut app = App::new(State::MenuSelect, true);
+        let mut monitors = get_monitor_info(true).unwrap();
+        let mut app_states = History::default();
+
+        let cell = cell_for(&monitors, &app, (5440, 540));
+        mouse(MouseEventKind::Down(MouseButton::Left), cell, &mut monitors, &mut app, &mut app_states);
+        assert!(app.drag.is_none());
+        assert_eq!(app.selected_idx, 0);
+    }
+}
+
+mod canvas {
+    use super::*;
+    use ratatui::layout::Rect;
+
+    #[test]
+    fn monitors_keep_their_proportions() {
+        let app = App::new(State::MonitorEdit, true);
+        let monitors = get_monitor_info(true).unwrap();
+
+        // wide and tall terminals both draw every monitor with the same scale both ways
+        for area in [Rect::new(0, 0, 200, 30), Rect::new(0, 0, 60, 60)] {
+            let transform = CanvasTransform::new(area, &monitors, &app);
+            for monitor in &monitors {
+                let (width, height) = transform.to_canvas_size(monitor.displayed_resolution);
+                let ratio = monitor.displayed_resolution.0 as f64 / monitor.displayed_resolution.1 as f64;
+                assert!((width / height - ratio).abs() < 0.001);
+            }
+            let (hdmi, dp) = (transform.to_canvas_size((2560, 1440)), transform.to_canvas_size((1920, 1080)));
+            assert!((hdmi.0 / dp.0 - 2560.0 / 1920.0).abs() < 0.001);
+        }
+    }
+
+    #[test]
+    fn layout_is_centred() {
+        let app = App::new(State::MonitorEdit, true);
+        let monitors = get_monitor_info(true).unwrap();
+        let transform = CanvasTransform::new(Rect::new(0, 0, 200, 100), &monitors, &app);
+        let (canvas_width, canvas_height) = transform.bounds();
+
+        let (left, top, right, bottom) = layout_bounds(&monitors);
+        let (x1, y1) = transform.to_canvas((left, top));
+        let (x2, y2) = transform.to_canvas((right, bottom));
+        assert!((x1 - (canvas_width - x2)).abs() < 0.001);
+        assert!((y2 - (canvas_height - y1)).abs() < 0.001);
+    }
+
+    #[test]
+    fn labels_fit_inside_monitors() {
+        let mut monitors = get_monitor_info(true).unwrap();
+        monitors.iter_mut().for_each(|m| m.edid = None);
+        assert_eq!(monitor_label(&monitors[0], 40), "HDMI-1* (2560x1440)");
+        assert_eq!(monitor_label(&monitors[0], 12), "HDMI-1*");
+        assert_eq!(monitor_label(&monitors[0], 6), "HDM…");
+        assert_eq!(monitor_label(&monitors[0], 2), "");
+
+        monitors[1].is_selected = true;
+        assert_eq!(monitor_label(&monitors[1], 8), "<DP-1>");
+        assert_eq!(monitor_label(&monitors[1], 7), "<DP…>");
+    }
+}
+
+mod zoom {
+    use super::*;
+
+    fn press(keys: &str, monitors: &mut Monitors, app: &mut App, app_states: &mut History) {
+        keys.chars().for_each(|c| handle_key_press(KeyCode::Char(c), monitors, app, app_states));
+    }
+
+    #[test]
+    fn zoom_in_and_back_out() {
+        let mut app = App::new(State::MonitorEdit, true);
+        let mut monitors = get_monitor_info(true).unwrap();
+        monitor_proximity(&mut monitors);
+        let mut app_states = History::default();
+
+        press("+", &mut monitors, &mut app, &mut app_states);
+        let (left, top, right, bottom) = app.view.unwrap();
+        assert_eq!((right - left, bottom - top), (4266, 960));
+        assert_eq!(((left + right) / 2, (top + bottom) / 2), (3200, 720));
+
+        // out past where everything fits goes back to fitting everything
+        press("--", &mut monitors, &mut app, &mut app_states);
+        assert_eq!(app.view, None);
+        assert!(app_states.undo.is_empty());
+    }
+
+    #[test]
+    fn pan_moves_a_quarter_of_the_view() {
+        let mut app = App::new(State::MonitorEdit, true);
+        let mut monitors = get_monitor_info(true).unwrap();
+        monitor_proximity(&mut monitors);
+        let mut app_states = History::default();
+
+        press("]}", &mut monitors, &mut app, &mut app_states);
+        assert_eq!(app.view, Some((1600, 360, 8000, 1800)));
+        press("0", &mut monitors, &mut app, &mut app_states);
+        assert_eq!(app.view, None);
+    }
+
+    #[test]
+    fn fit_selection_and_neighbours() {
+        let mut app = App::new(State::MonitorEdit, true);
+        let mut monitors = get_monitor_info(true).unwrap();
+        monitor_proximity(&mut monitors);
+        let mut app_states = History::default();
+
+        press("f", &mut monitors, &mut app, &mut app_states);
+        assert_eq!(app.view, Some((0, 0, 4480, 1440)));
+
+        // the canvas still maps back to the layout for the mouse
+        let transform = CanvasTransform::new(monitor_area(ratatui::layout::Rect::new(0, 0, 100, 40)), &monitors, &app);
+        let (x, _) = transform.to_canvas((4480, 0));
+        assert!(x <= transform.bounds().0);
+    }
+}
+
+mod text_edit {
+    use super::*;
+
+    fn type_text(text: &str, monitors: &mut Monitors, app: &mut App, app_states: &mut History) {
+        text.chars().for_each(|c| handle_key_press(KeyCode::Char(c), monitors, app, app_states));
+    }
+
+    fn clear(monitors: &mut Monitors, app: &mut App, app_states: &mut History) {
+        for _ in 0..app.text_input.len() {
+            handle_key_press(KeyCode::Backspace, monitors, app, app_states);
+        }
+    }
+
+    #[test]
+    fn type_a_position() {
+        let (mut app, mut monitors, mut app_states) = menu_at(1, MenuEntry::Position);
+        handle_key_press(KeyCode::Enter, &mut monitors, &mut app, &mut app_states);
+        assert_eq!(app.state, State::TextEdit);
+        assert_eq!(app.text_input, "2560,0");
+
+        clear(&mut monitors, &mut app, &mut app_states);
+        type_text("0,1440", &mut monitors, &mut app, &mut app_states);
+        handle_key_press(KeyCode::Enter, &mut monitors, &mut app, &mut app_states);
+
+        assert_eq!(app.state, State::MenuSelect);
+        assert_eq!(monitors[1].position, (0, 1440));
+        assert_eq!(monitors[1].up, Some(0));
+        assert_eq!(monitors[0].down, Some(1));
+        assert_eq!(app_states.undo.last().unwrap().label, "position 0,1440 on DP-1");
+    }
+
+    #[test]
+    fn type_a_scale() {
+        let (mut app, mut monitors, mut app_states) = menu_at(1, MenuEntry::Scale);
+        handle_key_press(KeyCode::Enter, &mut monitors, &mut app, &mut app_states);
+        clear(&mut monitors, &mut app, &mut app_states);
+        type_text("2", &mut monitors, &mut app, &mut app_states);
+        handle_key_press(KeyCode::Enter, &mut monitors, &mut app, &mut app_states);
+
+        assert_eq!(monitors[1].displayed_resolution, (960, 540));
+        assert_eq!(monitors[2].position, (3520, 0));
+        assert_eq!(monitors[1].right, Some(2));
+    }
+
+    #[test]
+    fn invalid_input_is_rejected() {
+        let (mut app, mut monitors, mut app_states) = menu_at(1, MenuEntry::Position);
+        let start = monitors.clone();
+        handle_key_press(KeyCode::Enter, &mut monitors, &mut app, &mut app_states);
+        clear(&mut monitors, &mut app, &mut app_states);
+
+        // command keys are typed, not run
+        type_text("qs", &mut monitors, &mut app, &mut app_states);
+        handle_key_press(KeyCode::Enter, &mut monitors, &mut app, &mut app_states);
+        assert_eq!(app.state, State::TextEdit);
+        assert!(app.input_error.is_some());
+
+        handle_key_press(KeyCode::Esc, &mut monitors, &mut app, &mut app_states);
+        assert_eq!(app.state, State::MenuSelect);
+        assert_eq!(monitors, start);
+        assert!(app_states.undo.is_empty());
+    }
+
+    #[test]
+    fn parse_typed_values() {
+        assert_eq!(parse_position("2560,0"), Ok((2560, 0)));
+        assert_eq!(parse_position(" 1920x1080 "), Ok((1920, 1080)));
+        assert!(parse_position("-10,0").is_err());
+        assert!(parse_position("2560").is_err());
+        assert_eq!(parse_scale("1.333"), Ok(1.333));
+        assert!(parse_scale("0").is_err());
+        assert!(parse_scale("big").is_err());
+    }
+}
+
+mod neighbours {
+    use super::*;
+
+    #[test]
+    fn pick_a_right_neighbour() {
+        let (mut app, mut monitors, mut app_states) = menu_at(0, MenuEntry::Right);
+        handle_key_press(KeyCode::Enter, &mut monitors, &mut app, &mut app_states);
+        assert_eq!(app.state, State::InfoEdit);
+        assert_eq!(neighbour_candidates(&monitors, 0), vec![1, 2]);
+
+        handle_key_press(KeyCode::Down, &mut monitors, &mut app, &mut app_states);
+        handle_key_press(KeyCode::Enter, &mut monitors, &mut app, &mut app_states);
+
+        assert_eq!(monitors[2].position, (2560, 0));
+        assert_eq!(monitors[1].position, (4480, 0));
+        assert_eq!(monitors[0].right, Some(2));
+        assert_eq!(monitors[2].right, Some(1));
+        assert_eq!(app_states.undo.last().unwrap().label, "place DP-2 right of HDMI-1");
+    }
+
+    #[test]
+    fn pick_a_neighbour_below() {
+        let (mut app, mut monitors, mut app_states) = menu_at(0, MenuEntry::Down);
+        handle_key_press(KeyCode::Enter, &mut monitors, &mut app, &mut app_states);
+        handle_key_press(KeyCode::Down, &mut monitors, &mut app, &mut app_states);
+        handle_key_press(KeyCode::Enter, &mut monitors, &mut app, &mut app_states);
+
+        assert_eq!(monitors[2].position, (0, 1440));
+        assert_eq!(monitors[1].position, (2560, 0));
+        assert_eq!(monitors[0].down, Some(2));
+        assert_eq!(monitors[2].up, Some(0));
+    }
+
+    #[test]
+    fn current_neighbour_is_left_alone() {
+        let (mut app, mut monitors, mut app_states) = menu_at(0, MenuEntry::Right);
+        let start = monitors.clone();
+        handle_key_press(KeyCode::Enter, &mut monitors, &mut app, &mut app_states);
+        handle_key_press(KeyCode::Enter, &mut monitors, &mut app, &mut app_states);
+
+        assert_eq!(monitors, start);
+        assert!(app_states.undo.is_empty());
+
+        handle_key_press(KeyCode::Esc, &mut monitors, &mut app, &mut app_states);
+        assert_eq!(app.state, State::MenuSelect);
+    }
+}
+
+mod menu_toggles {
+    use super::*;
+
+    #[test]
+    fn toggle_primary() {
+        let (mut app, mut monitors, mut app_states) = menu_at(1, MenuEntry::Primary);
+        handle_key_press(KeyCode::Enter, &mut monitors, &mut app, &mut app_states);
+        assert!(monitors[1].is_primary);
+        assert!(!monitors[0].is_primary);
+        assert_eq!(app.state, State::MenuSelect);
+
+        handle_key_press(KeyCode::Enter, &mut monitors, &mut app, &mut app_states);
+        assert!(monitors.iter().all(|m| !m.is_primary));
+        assert_eq!(app_states.undo.len(), 2);
+    }
+
+    #[test]
+    fn disable_from_menu_reflows_neighbours() {
+        let (mut app, mut monitors, mut app_states) = menu_at(1, MenuEntry::Enabled);
+        handle_key_press(KeyCode::Enter, &mut monitors, &mut app, &mut app_states);
+
+        assert!(!monitors[1].is_enabled);
+        assert_eq!(monitors[2].position, (2560, 0));
+        assert_eq!(monitors[0].right, Some(2));
+        assert_eq!(app.state, State::MonitorEdit);
+        assert_ne!(app.selected_idx, 1);
+        assert!(monitors.iter().all(|m| !m.is_selected));
+        assert_eq!(app_states.undo.last().unwrap().label, "disable DP-1");
+    }
+}
+
+mod connections {
+    use super::*;
+
+    fn press(key: KeyCode, monitors: &mut Monitors, app: &mut App, app_states: &mut History) {
+        handle_key_press(key, monitors, app, app_states);
+    }
+
+    #[test]
+    fn rows_include_disconnected_outputs() {
+        let monitors = get_monitor_info(true).unwrap();
+        let rows = connection_rows(&monitors, &parse_outputs(crate::debug::xrandr_debug::XRANDR_OUTPUT));
+
+        assert_eq!(rows.len(), 4);
+        assert_eq!(rows[0], ["HDMI-1", "on", "DELL U2719D", "2560x1440", "597x336mm 27.0\""].map(String::from));
+        assert_eq!(rows[3], ["HDMI-2", "disconnected", "-", "-", "-"].map(String::from));
+
+        // without details there's still a row per monitor
+        assert_eq!(connection_rows(&monitors, &[])[1][3], "-");
+    }
+
+    #[test]
+    fn only_monitors_can_be_picked() {
+        let mut app = App::new(State::MonitorEdit, true);
+        let mut monitors = get_monitor_info(true).unwrap();
+        monitor_proximity(&mut monitors);
+        let mut app_states = History::default();
+        press(KeyCode::Char('D'), &mut monitors, &mut app, &mut app_states);
+        for _ in 0..5 {
+            press(KeyCode::Down, &mut monitors, &mut app, &mut app_states);
+        }
+        assert_eq!(app.connected_monitor_id, 2);
+    }
+
+    #[test]
+    fn turn_on_below_selected() {
+        let mut app = App::new(State::MonitorEdit, true);
+        let mut monitors = get_monitor_info(true).unwrap();
+        monitor_proximity(&mut monitors);
+        let mut app_states = History::default();
+
+        // turn DP-2 off, then back on below HDMI-1
+        press(KeyCode::Char('D'), &mut monitors, &mut app, &mut app_states);
+        press(KeyCode::Down, &mut monitors, &mut app, &mut app_states);
+        press(KeyCode::Down, &mut monitors, &mut app, &mut app_states);
+        press(KeyCode::Enter, &mut monitors, &mut app, &mut app_states);
+        assert!(!monitors[2].is_enabled);
+
+        for _ in 0..3 {
+            press(KeyCode::Char('a'), &mut monitors, &mut app, &mut app_states);
+        }
+        assert_eq!(app.attach, AttachPoint::Beside(Dir::Down));
+        press(KeyCode::Enter, &mut monitors, &mut app, &mut app_states);
+
+        assert!(monitors[2].is_enabled);
+        assert_eq!(monitors[2].position, (0, 1440));
+        assert_eq!(monitors[0].down, Some(2));
+        assert_eq!(monitors[1].position, (2560, 0));
+    }
+
+    #[test]
+    fn turn_on_at_end_of_row_by_default() {
+        let mut app = App::new(State::MonitorEdit, true);
+        let mut monitors = get_monitor_info(true).unwrap();
+        monitor_proximity(&mut monitors);
+        let mut app_states = History::default();
+
+        press(KeyCode::Char('D'), &mut monitors, &mut app, &mut app_states);
+        press(KeyCode::Down, &mut monitors, &mut app, &mut app_states);
+        press(KeyCode::Enter, &mut monitors, &mut app, &mut app_states);
+        press(KeyCode::Enter, &mut monitors, &mut app, &mut app_states);
+
+        assert_eq!(monitors[1].position, (4480, 0));
+        assert_eq!(monitors[2].right, Some(1));
+    }
+}
@@ -0,0 +1,97 @@
+use std::fs;
+use std::path::Path;
+
+use crate::monitor::*;
+use crate::profile::*;
+use crate::watch::*;
+use crate::xrandr::*;
+use crate::tests::{temp_dir, MockBackend};
+
+fn set_status(drm_root: &Path, connector: &str, connected: bool) {
+    let dir = drm_root.join(connector);
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("status"), if connected { "connected\n" } else { "disconnected\n" }).unwrap();
+}
+
+#[test]
+fn read_fake_sysfs() {
+    let root = temp_dir("watch-sysfs");
+    set_status(&root, "card0-HDMI-A-1", true);
+    set_status(&root, "card0-DP-1", false);
+    fs::create_dir_all(root.join("version")).unwrap();
+
+    let status = read_connector_status(&root);
+    assert_eq!(status.len(), 2);
+    assert!(status["card0-HDMI-A-1"]);
+    assert!(!status["card0-DP-1"]);
+    fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn applies_matching_profile_on_hotplug() {
+    let root = temp_dir("watch-hotplug");
+    let drm_root = root.join("drm");
+    let profiles = root.join("profiles");
+    set_status(&drm_root, "card0-HDMI-A-1", true);
+    set_status(&drm_root, "card0-DP-1", false);
+
+    let mut laptop = get_monitor_info(true).unwrap();
+    laptop.truncate(1);
+    let mut docked = get_monitor_info(true).unwrap();
+    docked.truncate(2);
+    docked[1].position = (0, 0);
+    docked[0].position = (1920, 0);
+    reset_proximity(&mut docked);
+    Profile::from_monitors("docked", &docked).save(&profiles).unwrap();
+
+    let backend = MockBackend::new(laptop);
+    let mut watcher = Watcher::new(backend, drm_root.clone(), profiles.clone());
+
+    // nothing saved for the laptop on its own
+    let event = watcher.poll().unwrap();
+    assert!(matches!(event, Some(WatchEvent::NoMatch(_))));
+    assert_eq!(watcher.poll().unwrap(), None);
+    assert!(watcher.backend.applied.is_empty());
+
+    // dock
+    set_status(&drm_root, "card0-DP-1", true);
+    watcher.backend.monitors = get_monitor_info(true).unwrap();
+    watcher.backend.monitors.truncate(2);
+    monitor_proximity(&mut watcher.backend.monitors);
+
+    assert_eq!(watcher.poll().unwrap(), Some(WatchEvent::Applied("docked".to_string())));
+    assert_eq!(watcher.backend.applied.len(), 1);
+    let args = watcher.backend.applied[0].join(" ");
+    assert!(args.contains("--output HDMI-1 --primary --mode 2560x1440 --rate 60 --pos 1920x0"));
+    assert!(args.contains("--output DP-1 --mode 1920x1080 --rate 60 --pos 0x0"));
+
+    fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn failed_apply_is_retried() {
+    let root = temp_dir("watch-retry");
+    let drm_root = root.join("drm");
+    let profiles = root.join("profiles");
+    set_status(&drm_root, "card0-HDMI-A-1", true);
+
+    let mut monitors = get_monitor_info(true).unwrap();
+    monitors.truncate(1);
+    let mut saved = monitors.clone();
+    saved[0].framerate = 59.95;
+    Profile::from_monitors("laptop", &saved).save(&profiles).unwrap();
+
+    let mut backend = MockBackend::new(monitors);
+    backend.exit_code = 1;
+    backend.stderr = "xrandr: cannot find mode\n".to_string();
+    let mut watcher = Watcher::new(backend, drm_root.clone(), profiles.clone());
+    assert!(watcher.poll().is_err());
+
+    // nothing was plugged in since, but the profile still hasn't been applied
+    watcher.backend.exit_code = 0;
+    assert_eq!(watcher.poll().unwrap(), Some(WatchEvent::Applied("laptop".to_string())));
+    assert!(watcher.backend.applied.last().unwrap().join(" ").contains("--rate 59.95"));
+    assert_eq!(watcher.poll().unwrap(), None);
+
+    fs::remove_dir_all(&root).unwrap();
+}
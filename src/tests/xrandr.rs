@@ -0,0 +1,84 @@
+use std::fs;
+use std::io::ErrorKind;
+
+use crate::debug::xrandr_debug::*;
+use crate::xrandr::*;
+use crate::tests::temp_dir;
+
+#[test]
+fn parse_saved_query() {
+    let monitors = parse_monitor_info(XRANDR_OUTPUT_3_DIFF);
+    assert_eq!(monitors.len(), 3);
+    assert_eq!(monitors[2].name, "DP-2");
+    assert_eq!(monitors[2].resolution, (1920, 1200));
+    assert_eq!(monitors[2].position, (4480, 0));
+    assert!(monitors[0].is_primary);
+}
+
+#[test]
+fn parse_primary_output_that_is_off() {
+    let query = XRANDR_OUTPUT.replace(
+        "HDMI-1 connected primary 2560x1440+0+0 (normal left inverted right x axis y axis) 597mm x 336mm",
+        "HDMI-1 connected primary (normal left inverted right x axis y axis)",
+    );
+    let monitors = parse_monitor_info(&query);
+    assert_eq!(monitors.len(), 3);
+    assert!(!monitors[0].is_enabled);
+    assert_eq!(monitors[0].position, (0, 0));
+    assert!(monitors[1].is_enabled);
+}
+
+#[test]
+fn parse_scaled_outputs() {
+    // DP-1 and DP-2, the last output, both shown at half size with xrandr --scale 0.5
+    let query = XRANDR_OUTPUT
+        .replace("DP-1 connected 1920x1080+2560+0", "DP-1 connected 960x540+2560+0")
+        .replace("DP-2 connected 1920x1080+4480+0", "DP-2 connected 960x540+3520+0");
+    let monitors = parse_monitor_info(&query);
+    for monitor in &monitors[1..] {
+        assert_eq!(monitor.resolution, (1920, 1080));
+        assert_eq!(monitor.displayed_resolution, (960, 540));
+        assert_eq!(monitor.scale, 2.0);
+        assert!(output_args(monitor).join(" ").ends_with("--scale 0.50"));
+    }
+    assert_eq!(monitors[0].scale, 1.0);
+}
+
+#[test]
+fn read_query_from_file() {
+    let dir = temp_dir("xrandr-input");
+    let path = dir.join("xrandr.txt");
+    fs::write(&path, XRANDR_OUTPUT_3_DIFF).unwrap();
+
+    let mut backend = make_backend(false, Some(&path), false);
+    let monitors = backend.query().unwrap();
+    assert_eq!(monitors[2].resolution, (1920, 1200));
+    assert_eq!(monitors[1].right, Some(2));
+
+    fs::write(&path, "Screen 0: minimum 320 x 200\n").unwrap();
+    assert_eq!(read_monitor_info(&path).unwrap_err().kind(), ErrorKind::InvalidData);
+    let missing = read_monitor_info(&dir.join("missing.txt")).unwrap_err();
+    assert!(missing.to_string().contains("missing.txt"));
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn dry_run_only_reports() {
+    let mut backend = make_backend(true, None, true);
+    assert_eq!(backend.query().unwrap().len(), 3);
+
+    let result = run_apply(&mut backend, &["--output".to_string(), "DP-1".to_string(), "--off".to_string()]);
+    assert!(result.success);
+    assert_eq!(result.stdout, "dry run, not applied: xrandr --output DP-1 --off\n");
+}
+
+#[test]
+fn parse_every_output() {
+    let outputs = parse_outputs(XRANDR_OUTPUT);
+    assert_eq!(outputs.len(), 4);
+    assert_eq!(outputs[0], OutputInfo { name: "HDMI-1".to_string(), connected: true, physical_size: Some((597, 336)), preferred: Some((2560, 1440)) });
+    assert_eq!(outputs[3], OutputInfo { name: "HDMI-2".to_string(), connected: false, physical_size: None, preferred: None });
+
+    // disconnected outputs still aren't monitors
+    assert_eq!(parse_monitor_info(XRANDR_OUTPUT).len(), 3);
+}
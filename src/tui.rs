@@ -1,32 +1,46 @@
+use crate::diff::*;
+use crate::export::*;
+use crate::history::*;
+use crate::hooks::*;
+use crate::import::*;
+use crate::json::*;
+use crate::keymap::*;
 use crate::monitor::*;
+use crate::profile::*;
+use crate::record::*;
 use crate::xrandr::*;
-use crate::{App, Dir, FocusedWindow, MenuEntry, State};
+use crate::{Align, App, AttachPoint, Dir, Drag, FocusedWindow, MenuEntry, ResetTarget, State};
 
-use std::io;
-use std::process::Command;
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+use std::rc::Rc;
+use std::sync::atomic::Ordering;
+use std::thread;
+use std::time::{Duration, Instant};
 
 use ratatui::{
     backend::CrosstermBackend,
-    layout::{Constraint, Direction, Layout, Rect},
+    layout::{Constraint, Direction, Layout, Position, Rect},
     style::{Color, Style, Modifier},
     text::{Span, Line},
-    widgets::{Block, Borders, Paragraph, Wrap, canvas::{Canvas, Rectangle}},
+    widgets::{Block, Borders, Clear, Paragraph, Wrap, canvas::{Canvas, Rectangle}},
     Terminal,
     Frame,
 };
 
 use crossterm::{
-    event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode},
+    event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyModifiers, MouseButton, MouseEvent, MouseEventKind},
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
 
-fn main_loop<B: ratatui::backend::Backend>(terminal: &mut Terminal<B>, mut monitors: Monitors, debug: bool, app_states: &mut Vec<Monitors>) -> io::Result<()> {
-    // initial setup
-    let mut app = App::new(State::MonitorEdit, debug);
+// how long an applied layout stays without being confirmed
+pub const REVERT_TIMEOUT: Duration = Duration::from_secs(15);
 
-    // push a copy of the initial state to the history
-    app_states.push((*monitors.clone()).to_vec());
+// remember the layout we started with and select the first enabled monitor
+pub fn start_session(monitors: &Monitors, app: &mut App) {
+    app.startup_state = Some(monitors.clone());
 
     let mut selected_idx = 0;
     while !monitors[selected_idx].is_enabled && selected_idx < monitors.len() {
@@ -35,38 +49,107 @@ fn main_loop<B: ratatui::backend::Backend>(terminal: &mut Terminal<B>, mut monit
 
     app.selected_idx = selected_idx;
     app.current_idx = selected_idx;
+}
+
+fn main_loop<B: ratatui::backend::Backend>(terminal: &mut Terminal<B>, mut monitors: Monitors, mut app: App, app_states: &mut History) -> io::Result<Monitors> {
+    start_session(&monitors, &mut app);
 
     loop {
-        terminal.draw(|f| render_ui::<B>(f, &app, &monitors))?;
+        terminal.draw(|f| render_ui(f, &app, &monitors, app_states))?;
+
+        // poll so the revert countdown keeps ticking without input
+        if event::poll(Duration::from_millis(250))? {
+            match event::read()? {
+                Event::Key(key) => {
+                    let key = Key::new(key.code, key.modifiers);
+                    handle_key(key, &mut monitors, &mut app, app_states);
+                    if let Some(name) = key.name() {
+                        record_step(&mut app, |dir| record_key(dir, &name, &monitors));
+                    }
+                }
+                Event::Mouse(mouse) => {
+                    let size = terminal.size()?;
+                    let area = Rect::new(0, 0, size.width, size.height);
+                    handle_mouse_event(mouse, area, &mut monitors, &mut app, app_states);
+                    if let Some(name) = mouse_name(&mouse, area) {
+                        record_step(&mut app, |dir| record_key(dir, &name, &monitors));
+                    }
+                }
+                _ => {}
+            }
+        }
 
-        if let Event::Key(key) = event::read()? {
-            handle_key_press(key.code, &mut monitors, &mut app, app_states);
+        // a revert from the countdown is recorded like a key, so a replay does it at the same point
+        let waiting = matches!(app.state, State::ConfirmApply);
+        check_revert_timeout(&mut monitors, &mut app, app_states);
+        check_apply_done(&mut monitors, &mut app);
+        if waiting && !matches!(app.state, State::ConfirmApply) {
+            record_step(&mut app, |dir| record_key(dir, TIMEOUT_KEY, &monitors));
         }
 
         if matches!(app.state, State::Quit) {
-            return Ok(());
+            return Ok(monitors);
         }
     }
 }
 
+// a recording that can't be written to is stopped, saying why, rather than quietly missing steps
+fn record_step(app: &mut App, write: impl FnOnce(&Path) -> io::Result<()>) {
+    let Some(dir) = &app.record else { return };
+    if let Err(err) = write(dir) {
+        app.record_error = Some(format!("Stopped recording to {}: {}", dir.display(), err));
+        app.record = None;
+    }
+}
+
+// run the tui with the options set on app
+// - with print_json the tui draws on stderr, leaving stdout free for the final layout
+pub fn run_tui(mut app: App, import: Option<&str>) -> Result<(), io::Error> {
+    let print_json = app.print_json;
 
-pub fn run_tui(debug: bool) -> Result<(), io::Error> {
     // Get monitor information
-    match get_monitor_info(debug) {
+    match app.backend().query() {
         Ok(mut monitors) => {
+            // start from an existing xrandr command if we were given one
+            let mut import_text: Option<String> = None;
+            if let Some(path) = import {
+                match fs::read_to_string(path).and_then(|text| import_xrandr_command(&mut monitors, &text).map(|_| text)) {
+                    Ok(text) => import_text = Some(text),
+                    Err(err) => {
+                        println!("FATAL: Could not import layout from {}", path);
+                        println!("Error: {}", err);
+                        return Ok(());
+                    }
+                }
+            }
+
+            // details for the connections popup, which just shows less without them
+            let query = query_output(app.debug, app.input.as_deref());
+            app.outputs = query.as_ref().map(|query| parse_outputs(query)).unwrap_or_default();
+
+            // save what the session starts from before anything can go wrong
+            if let Some(dir) = &app.record {
+                let started = query.and_then(|query| start_recording(dir, &query, import_text.as_deref(), &app.keymap));
+                if let Err(err) = started {
+                    println!("FATAL: Could not start recording to {}", dir.display());
+                    println!("Error: {}", err);
+                    return Ok(());
+                }
+            }
+
             // Setup terminal
             enable_raw_mode()?;
-            let mut stdout = io::stdout();
-            execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
-            let backend = CrosstermBackend::new(stdout);
+            let mut output: Box<dyn Write> = if print_json { Box::new(io::stderr()) } else { Box::new(io::stdout()) };
+            execute!(output, EnterAlternateScreen, EnableMouseCapture)?;
+            let backend = CrosstermBackend::new(output);
             let mut terminal = Terminal::new(backend)?;
 
             monitor_proximity(&mut monitors);
 
-            let mut app_states: Vec<Monitors> = Vec::new();
+            let mut app_states = History::default();
 
             // Run the main loop
-            let _res = main_loop(&mut terminal, monitors, debug, &mut app_states);
+            let res = main_loop(&mut terminal, monitors, app, &mut app_states);
 
             // Restore terminal
             disable_raw_mode()?;
@@ -77,15 +160,20 @@ pub fn run_tui(debug: bool) -> Result<(), io::Error> {
             )?;
             terminal.show_cursor()?;
 
+            if print_json {
+                print!("{}", monitors_to_json(&res?));
+                return Ok(());
+            }
+
         }
         Err(err) => {
-            println!("");
+            println!();
             println!("FATAL: Problem with xrandr, can't be run!");
             println!("Error: {:?}", err);
         }
     }
 
-    println!("");
+    println!();
 
     Ok(())
 }
@@ -113,56 +201,375 @@ fn render_debug_popup(f: &mut Frame, monitors: &Monitors) {
     f.render_widget(paragraph, popup_area);
 }
 
-fn render_connections_popup(f: &mut Frame, monitors: &Monitors, app: App) {
+fn render_export_popup(f: &mut Frame, monitors: &Monitors, app: &App) {
     // Create a centered pop-up
-    let popup_area = centered_rect(60, 20, f.area());
+    let popup_area = centered_rect(60, 40, f.area());
+
+    let mut info: Vec<Line> = Vec::new();
+    if let Some(err) = app.export_error {
+        info.push(Line::from(Span::styled(format!("Export failed: {}", err), Style::default().fg(Color::Red))));
+    } else {
+        info.push(Line::from(format!("Wrote ./{} and ./{}", SCRIPT_FILE, XORG_CONF_FILE)));
+    }
+    info.push(Line::from(""));
+    for line in to_shell_script(monitors).lines() {
+        info.push(Line::from(Span::styled(line.to_string(), Style::default().fg(Color::Yellow))));
+    }
+
+    let export_block = Block::default()
+        .title("Export layout")
+        .borders(Borders::ALL)
+        .style(Style::default().fg(Color::LightBlue));
+
+    let export_paragraph = Paragraph::new(info)
+        .block(export_block)
+        .style(Style::default().fg(Color::White))
+        .wrap(Wrap { trim: false });
+
+    f.render_widget(export_paragraph, popup_area);
+}
+
+// lines describing the hooks run for the last apply, filled in as they finish
+fn generate_hook_output(log: &HookLog) -> Vec<Line<'static>> {
+    let log = log.lock().unwrap();
+    let mut info: Vec<Line> = Vec::new();
+    if log.is_empty() {
+        return info;
+    }
+    info.push(Line::from(""));
+    info.push(Line::from(Span::styled("hooks:", Style::default().add_modifier(Modifier::BOLD))));
+    for hook in log.iter() {
+        info.push(Line::from(Span::styled(format!("{} $ {} ({})", hook.stage, hook.command, hook.status), Style::default().fg(Color::Yellow))));
+        for line in hook.output.lines() {
+            info.push(Line::from(line.to_string()));
+        }
+    }
+    info
+}
+
+// lines describing what xrandr did with the last layout sent to it
+fn generate_apply_result(result: &ApplyResult) -> Vec<Line<'static>> {
+    let mut info = Vec::new();
+    // a staged apply runs one command per stage
+    for command in result.command.lines() {
+        info.push(Line::from(Span::styled(format!("$ {}", command), Style::default().fg(Color::Yellow))));
+    }
+    info.push(Line::from(format!("Status: {}", result.status)));
+    for (title, output) in [("stdout", &result.stdout), ("stderr", &result.stderr)] {
+        if output.trim().is_empty() { continue; }
+        info.push(Line::from(""));
+        info.push(Line::from(Span::styled(format!("{}:", title), Style::default().add_modifier(Modifier::BOLD))));
+        for line in output.lines() {
+            info.push(Line::from(line.to_string()));
+        }
+    }
+    info
+}
+
+// before/after table of the outputs that are about to change
+fn render_apply_preview_popup(f: &mut Frame, monitors: &Monitors, app: &App, app_states: &History) {
+    let popup_area = centered_rect(70, 40, f.area());
+
+    let changes = diff_layouts(&displayed_layout(app, app_states).unwrap_or_default(), monitors);
+    let width = changes.iter().map(|c| c.name.len()).max().unwrap_or(0).max(6);
+    let before_width = changes.iter().map(|c| c.before.len()).max().unwrap_or(0).max(6);
+
+    let mut info: Vec<Line> = Vec::new();
+    if changes.is_empty() {
+        info.push(Line::from("Nothing has changed since the layout was last applied"));
+        let hint = app.keymap.hint(&[(&[Action::Select, Action::Back], "Close")]);
+        info.push(Line::from(Span::styled(hint, Style::default().fg(Color::Yellow))));
+    } else {
+        let hint = app.keymap.hint(&[
+            (&[Action::Select, Action::Apply, Action::Yes], "Apply"),
+            (&[Action::Back, Action::No], "Cancel"),
+        ]);
+        info.push(Line::from(Span::styled(hint, Style::default().fg(Color::Yellow))));
+        info.push(Line::from(""));
+        info.push(Line::from(Span::styled(
+            format!("{:<width$}  {:<before_width$}  {}", "Output", "Before", "After"),
+            Style::default().add_modifier(Modifier::BOLD),
+        )));
+        for change in &changes {
+            info.push(Line::from(vec![
+                Span::raw(format!("{:<width$}  ", change.name)),
+                Span::styled(format!("{:<before_width$}  ", change.before), Style::default().fg(Color::DarkGray)),
+                Span::styled(change.after.clone(), Style::default().fg(Color::LightGreen)),
+            ]));
+        }
+        info.push(Line::from(""));
+        info.push(Line::from(Span::styled(format!("$ xrandr {}", apply_args(monitors, app, app_states).join(" ")), Style::default().fg(Color::Yellow))));
+    }
+
+    let preview_block = Block::default()
+        .title("Apply layout")
+        .borders(Borders::ALL)
+        .style(Style::default().fg(Color::LightBlue));
 
-    let mut iterator = monitors.iter();
-    let mut args: Vec<(String, bool)> = Vec::new();
-    while let Some(element) = iterator.next() {
-        args.push((element.name.to_string(), element.is_enabled));
+    let preview_paragraph = Paragraph::new(info)
+        .block(preview_block)
+        .style(Style::default().fg(Color::White))
+        .wrap(Wrap { trim: false });
+
+    f.render_widget(Clear, popup_area);
+    f.render_widget(preview_paragraph, popup_area);
+}
+
+fn render_confirm_popup(f: &mut Frame, app: &App) {
+    let popup_area = centered_rect(60, 40, f.area());
+
+    let question = match app.revert_deadline {
+        Some(deadline) => format!("Keep this layout? Reverting in {}s", deadline.saturating_duration_since(Instant::now()).as_secs() + 1),
+        None => "Keep this layout? It's a dry run, so it won't revert on its own".to_string(),
+    };
+
+    let mut info = vec![
+        Line::from(question),
+        Line::from(Span::styled(
+            app.keymap.hint(&[(&[Action::Select, Action::Yes], "Keep"), (&[Action::Back, Action::No], "Revert")]),
+            Style::default().fg(Color::Yellow),
+        )),
+        Line::from(""),
+    ];
+    if let Some(result) = &app.apply_result {
+        info.extend(generate_apply_result(result));
     }
+    info.extend(generate_hook_output(&app.hook_log));
+
+    let confirm_block = Block::default()
+        .title("Confirm layout")
+        .borders(Borders::ALL)
+        .style(Style::default().fg(Color::LightGreen));
+
+    let confirm_paragraph = Paragraph::new(info)
+        .block(confirm_block)
+        .style(Style::default().fg(Color::White))
+        .wrap(Wrap { trim: false })
+        .scroll((app.result_scroll, 0));
+
+    f.render_widget(Clear, popup_area);
+    f.render_widget(confirm_paragraph, popup_area);
+}
+
+fn render_apply_result_popup(f: &mut Frame, app: &App) {
+    let popup_area = centered_rect(60, 40, f.area());
+
+    let mut info = app.apply_result.as_ref().map(generate_apply_result).unwrap_or_default();
+    if matches!(app.state, State::Applying) {
+        let hint = app.keymap.hint(&[(&[Action::Back], "Cancel hooks"), (&[Action::Quit], "Quit")]);
+        info.push(Line::from(Span::styled(hint, Style::default().fg(Color::Yellow))));
+        info.push(Line::from("Running hooks and sending the layout to xrandr..."));
+    }
+    info.extend(generate_hook_output(&app.hook_log));
+    let (title, color) = match &app.apply_result {
+        _ if matches!(app.state, State::Applying) => ("Applying layout", Color::LightBlue),
+        Some(result) if result.success => ("Apply succeeded", Color::LightGreen),
+        _ => ("Apply failed", Color::LightRed),
+    };
+
+    let result_block = Block::default()
+        .title(title)
+        .borders(Borders::ALL)
+        .style(Style::default().fg(color));
+
+    let result_paragraph = Paragraph::new(info)
+        .block(result_block)
+        .style(Style::default().fg(Color::White))
+        .wrap(Wrap { trim: false })
+        .scroll((app.result_scroll, 0));
+
+    f.render_widget(Clear, popup_area);
+    f.render_widget(result_paragraph, popup_area);
+}
+
+fn render_reset_popup(f: &mut Frame, app: &App) {
+    let popup_area = centered_rect(60, 20, f.area());
+
+    let options = [
+        (ResetTarget::LastApplied, "Last applied layout", app.applied_state.is_some()),
+        (ResetTarget::Startup, "Layout as found at startup", true),
+    ];
+
+    let info: Vec<Line> = options
+        .iter()
+        .map(|(target, label, available)| {
+            let mut style = Style::default();
+            if !available {
+                style = style.add_modifier(Modifier::DIM);
+            }
+            if *target == app.reset_target {
+                style = style.fg(Color::Yellow);
+            }
+            let label = if *available { label.to_string() } else { format!("{} (nothing applied yet)", label) };
+            Line::from(Span::styled(label, style))
+        })
+        .collect();
+
+    let reset_block = Block::default()
+        .title("Reset to")
+        .borders(Borders::ALL)
+        .style(Style::default().fg(Color::LightBlue));
+
+    let reset_paragraph = Paragraph::new(info)
+        .block(reset_block)
+        .style(Style::default().fg(Color::White))
+        .wrap(Wrap { trim: true });
+
+    f.render_widget(reset_paragraph, popup_area);
+}
+
+fn render_history_popup(f: &mut Frame, monitors: &Monitors, app: &App, app_states: &History) {
+    let popup_area = centered_rect(80, 60, f.area());
+    let chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(40), Constraint::Percentage(60)].as_ref())
+        .split(popup_area);
 
-    let info: Vec<Line> = args
+    let timeline = app_states.timeline(monitors);
+    let info: Vec<Line> = timeline
         .iter()
         .enumerate()
-        .map(|(i, mon)| {
-            Line::from(vec![
-                Span::styled(
-                    format!("{}: {}", mon.0, if mon.1 {"connected"} else {"disconnected"}),
-                    if i == app.connected_monitor_id {  Style::default().fg(Color::Yellow) } else { Style::default() }
-                )
-            ])
+        .map(|(i, entry)| {
+            let mut style = Style::default();
+            if i == app_states.position() {
+                style = style.add_modifier(Modifier::BOLD);
+            }
+            if i == app.history_idx {
+                style = style.fg(Color::Yellow);
+            }
+            // anything after the current layout can only be redone
+            if i > app_states.position() {
+                style = style.add_modifier(Modifier::DIM);
+            }
+            let marker = if i == app_states.position() { "*" } else { " " };
+            Line::from(Span::styled(format!("{}{}: {}", marker, i, entry.label), style))
         })
         .collect();
 
+    // keep the highlighted entry in view
+    let visible = chunks[0].height.saturating_sub(2) as usize;
+    let scroll = (app.history_idx + 1).saturating_sub(visible) as u16;
+
+    let history_block = Block::default()
+        .title("History")
+        .borders(Borders::ALL)
+        .style(Style::default().fg(Color::LightBlue));
+
+    let history_paragraph = Paragraph::new(info)
+        .block(history_block)
+        .style(Style::default().fg(Color::White))
+        .scroll((scroll, 0));
+
+    let preview_block = Block::default()
+        .title("Preview")
+        .borders(Borders::ALL)
+        .style(Style::default().fg(Color::LightBlue));
+    let preview_area = preview_block.inner(chunks[1]);
+
+    f.render_widget(Clear, popup_area);
+    f.render_widget(history_paragraph, chunks[0]);
+    f.render_widget(preview_block, chunks[1]);
+    // the preview shows all of each layout, whatever the main canvas is zoomed to
+    if let Some(entry) = timeline.get(app.history_idx) {
+        let unzoomed = App { view: None, drag: None, ..app.clone() };
+        draw_monitors(f, preview_area, &entry.monitors, &unzoomed);
+    }
+}
+
+// one row per output for the connections popup: name, state, model, preferred mode and size
+// - monitors first, in the order they're picked from, then anything with nothing plugged in
+pub fn connection_rows(monitors: &Monitors, outputs: &[OutputInfo]) -> Vec<[String; 5]> {
+    let details = |output: Option<&OutputInfo>| {
+        let preferred = output.and_then(|o| o.preferred).map_or("-".to_string(), |(w, h)| format!("{}x{}", w, h));
+        // diagonal in inches, the way screens are sold
+        let size = output.and_then(|o| o.physical_size).map_or("-".to_string(), |(w, h)| {
+            format!("{}x{}mm {:.1}\"", w, h, ((w * w + h * h) as f64).sqrt() / 25.4)
+        });
+        (preferred, size)
+    };
+
+    let mut rows: Vec<[String; 5]> = monitors.iter().map(|monitor| {
+        let (preferred, size) = details(outputs.iter().find(|o| o.name == monitor.name));
+        [
+            monitor.name.clone(),
+            if monitor.is_enabled { "on" } else { "off" }.to_string(),
+            monitor.edid.as_ref().map_or("-".to_string(), |edid| edid.model_name()),
+            preferred,
+            size,
+        ]
+    }).collect();
+    for output in outputs.iter().filter(|o| !monitors.iter().any(|m| m.name == o.name)) {
+        let (preferred, size) = details(Some(output));
+        let state = if output.connected { "off" } else { "disconnected" };
+        rows.push([output.name.clone(), state.to_string(), "-".to_string(), preferred, size]);
+    }
+    rows
+}
+
+fn describe_attach_point(monitors: &Monitors, app: &App) -> String {
+    let name = &monitors[app.selected_idx].name;
+    match app.attach {
+        AttachPoint::EndOfRow => "at the end of the first row".to_string(),
+        AttachPoint::Beside(Dir::Right) => format!("right of {}", name),
+        AttachPoint::Beside(Dir::Left) => format!("left of {}", name),
+        AttachPoint::Beside(Dir::Down) => format!("below {}", name),
+        AttachPoint::Beside(Dir::Up) => format!("above {}", name),
+    }
+}
+
+fn render_connections_popup(f: &mut Frame, monitors: &Monitors, app: &App) {
+    // Create a centered pop-up
+    let popup_area = centered_rect(80, 40, f.area());
+
+    let header = ["Output", "State", "Model", "Preferred", "Size"].map(String::from);
+    let rows = connection_rows(monitors, &app.outputs);
+    let widths: Vec<usize> = (0..5).map(|col| rows.iter().chain([&header]).map(|row| row[col].chars().count()).max().unwrap_or(0)).collect();
+    let format_row = |row: &[String; 5]| row.iter().zip(&widths).map(|(cell, width)| format!("{:<width$}", cell, width = width)).collect::<Vec<_>>().join("  ");
+
+    let mut info: Vec<Line> = vec![
+        Line::from(Span::styled(
+            app.keymap.hint(&[
+                (&[Action::Select], "Turn on/off"),
+                (&[Action::Attach], "Change where monitors are turned on"),
+                (&[Action::Back], "Close"),
+            ]),
+            Style::default().fg(Color::Yellow),
+        )),
+        Line::from(format!("Monitors turned on go {}", describe_attach_point(monitors, app))),
+        Line::from(""),
+        Line::from(Span::styled(format_row(&header), Style::default().add_modifier(Modifier::BOLD))),
+    ];
+    for (i, row) in rows.iter().enumerate() {
+        // only monitors can be picked, the rest are there to show what xrandr can see
+        let style = if i == app.connected_monitor_id {
+            Style::default().fg(Color::Yellow)
+        } else if i >= monitors.len() {
+            Style::default().fg(Color::DarkGray)
+        } else {
+            Style::default()
+        };
+        info.push(Line::from(Span::styled(format_row(row), style)));
+    }
+
     let connection_block = Block::default()
-        .title("Connected monitors")
+        .title("Outputs")
         .borders(Borders::ALL)
         .style(Style::default().fg(Color::LightBlue));
 
     let connection_paragraph = Paragraph::new(info)
         .block(connection_block)
         .style(Style::default().fg(Color::White))
-        .wrap(ratatui::widgets::Wrap { trim: true });
+        .wrap(ratatui::widgets::Wrap { trim: false });
 
+    f.render_widget(Clear, popup_area);
     f.render_widget(connection_paragraph, popup_area);
 }
 
-fn render_help_popup(f: &mut Frame) {
-    // help window with commands
-    let help_popup_area = centered_rect(60, 20, f.area());
-    let commands = {[
-        ("?", "help"),
-        ("<Enter>", "Edit selected monitor information"),
-        ("<Esc>", "Stop editing"),
-        ("m", "Enter monitor mode"),
-        ("r", "Reset to previously saved state (UNIMPLEMENTED)"),
-        ("s", "Apply saved changes"),
-        ("u", "Undo last change"),
-        ("d", "Preview xrandr command"),
-        ("D", "Connect/disconnect monitors"),
-    ]};
+fn render_help_popup(f: &mut Frame, app: &App) {
+    // help window with commands, from the keys in use
+    let help_popup_area = centered_rect(70, 80, f.area());
+    let mut commands = app.keymap.help();
+    commands.insert(commands.len().min(3), ("<Mouse>".to_string(), "Click a monitor to select it, drag it to move it".to_string()));
 
     let info: Vec<Line> = commands
         .iter()
@@ -189,8 +596,9 @@ fn render_help_popup(f: &mut Frame) {
     f.render_widget(info_paragraph, help_popup_area);
 }
 
-fn render_main_ui(f: &mut Frame, app: &App, monitors: &Monitors) {
-    let chunks = Layout::default()
+// monitor canvas on top, info below
+fn main_chunks(area: Rect) -> Rc<[Rect]> {
+    Layout::default()
         .direction(Direction::Vertical)
         .constraints(
             [
@@ -198,10 +606,28 @@ fn render_main_ui(f: &mut Frame, app: &App, monitors: &Monitors) {
                 Constraint::Percentage(30)
             ]
                 .as_ref())
-        .split(f.area());
+        .split(area)
+}
 
+// where the monitors are drawn, inside the border of the top chunk
+pub fn monitor_area(area: Rect) -> Rect {
+    Block::default().borders(Borders::ALL).inner(main_chunks(area)[0])
+}
+
+fn render_main_ui(f: &mut Frame, app: &App, monitors: &Monitors) {
+    let chunks = main_chunks(f.area());
+
+    // say how far in we are, so a zoomed canvas isn't mistaken for the whole layout
+    let title = match app.view {
+        Some(view) => {
+            let area = monitor_area(f.area());
+            let zoom = CanvasTransform::fit(area, view).scale / CanvasTransform::fit(area, layout_bounds(monitors)).scale;
+            format!("Monitors (zoom {:.1}x, 0 to fit)", zoom)
+        }
+        None => "Monitors".to_string(),
+    };
     let monitor_block = Block::default()
-        .title("Monitors")
+        .title(title)
         .borders(Borders::ALL)
         .style(Style::default().fg(if matches!(app.focused_window, FocusedWindow::MonitorList) {
             if matches!(app.state, State::MonitorEdit) {
@@ -216,12 +642,12 @@ fn render_main_ui(f: &mut Frame, app: &App, monitors: &Monitors) {
     let monitor_area = monitor_block.inner(chunks[0]);
     f.render_widget(monitor_block, chunks[0]);
 
-    draw_monitors(f, monitor_area, &monitors, *app);
+    draw_monitors(f, monitor_area, monitors, app);
 
-    let info = generate_monitor_info(&monitors, *app);
+    let info = generate_monitor_info(monitors, app);
 
     // dim monitor info if not currently being modified
-    let info_block_style = if matches!(app.state, State::MenuSelect) {
+    let info_block_style = if matches!(app.state, State::MenuSelect | State::TextEdit) {
         Style::default().fg(Color::LightMagenta)
     } else if matches!(app.state, State::InfoEdit) {
         Style::default().fg(Color::LightMagenta).add_modifier(Modifier::DIM)
@@ -230,7 +656,7 @@ fn render_main_ui(f: &mut Frame, app: &App, monitors: &Monitors) {
     };
 
     let info_block = Block::default()
-            .title("Monitor info: ".to_string() + &monitors[app.selected_idx].name)
+            .title("Monitor info: ".to_string() + &monitors[app.selected_idx].display_name())
             .borders(Borders::ALL)
             .style(info_block_style);
 
@@ -239,7 +665,8 @@ fn render_main_ui(f: &mut Frame, app: &App, monitors: &Monitors) {
         .style(Style::default().fg(Color::White))
         .wrap(ratatui::widgets::Wrap { trim: true });
 
-    if matches!(app.menu_entry, MenuEntry::Framerate | MenuEntry::Resolution) && matches!(app.state, State::MenuSelect | State::InfoEdit) {
+    let picks_from_list = matches!(app.menu_entry, MenuEntry::Framerate | MenuEntry::Resolution) || menu_direction(app.menu_entry).is_some();
+    if picks_from_list && matches!(app.state, State::MenuSelect | State::InfoEdit) {
         let bottom_chunks = Layout::default()
             .direction(Direction::Horizontal)
             .constraints(
@@ -249,8 +676,15 @@ fn render_main_ui(f: &mut Frame, app: &App, monitors: &Monitors) {
                     .as_ref())
             .split(chunks[1]);
 
-        let extra_info = generate_extra_info(&monitors, *app);
-        let title = if matches!(app.menu_entry, MenuEntry::Framerate) {"Framerate"} else {"Resolution"};
+        let extra_info = generate_extra_info(monitors, app);
+        let title = match app.menu_entry {
+            MenuEntry::Framerate => "Framerate",
+            MenuEntry::Resolution => "Resolution",
+            MenuEntry::Left => "Put on the left",
+            MenuEntry::Down => "Put below",
+            MenuEntry::Up => "Put above",
+            _ => "Put on the right",
+        };
 
         // dim extra info if not currently being modified
         let extra_block_style = if matches!(app.state, State::InfoEdit) {
@@ -277,78 +711,215 @@ fn render_main_ui(f: &mut Frame, app: &App, monitors: &Monitors) {
     }
 }
 
-fn render_ui<B: ratatui::backend::Backend>(f: &mut Frame, app: &App, monitors: &Monitors) {
+fn render_ui(f: &mut Frame, app: &App, monitors: &Monitors, app_states: &History) {
     match app.state {
         State::DebugPopup       => render_debug_popup(f, monitors),
-        State::HelpPopup        => render_help_popup(f),
-        State::ConnectionPopup  => render_connections_popup(f, monitors, *app),
+        State::HelpPopup        => render_help_popup(f, app),
+        State::ConnectionPopup  => render_connections_popup(f, monitors, app),
+        State::ExportPopup      => render_export_popup(f, monitors, app),
+        State::ResetPopup       => render_reset_popup(f, app),
+        State::HistoryPopup     => render_history_popup(f, monitors, app, app_states),
+        State::ApplyPreview     => {
+            render_main_ui(f, app, monitors);
+            render_apply_preview_popup(f, monitors, app, app_states);
+        }
+        State::ConfirmApply     => {
+            render_main_ui(f, app, monitors);
+            render_confirm_popup(f, app);
+        }
+        State::Applying | State::ApplyResult => {
+            render_main_ui(f, app, monitors);
+            render_apply_result_popup(f, app);
+        }
         _                       => render_main_ui(f, app, monitors),
     }
 }
 
-pub fn handle_key_press(key: KeyCode, mut monitors: &mut Monitors, mut app: &mut App, app_states: &mut Vec<Monitors>) {
-    match key {
+pub fn handle_key_press(key: KeyCode, monitors: &mut Monitors, app: &mut App, app_states: &mut History) {
+    handle_key(Key::from(key), monitors, app, app_states);
+}
+
+// look the key up in the keymap and do whatever it's bound to
+pub fn handle_key(key: Key, monitors: &mut Monitors, app: &mut App, app_states: &mut History) {
+    // keys are typed into the field, not taken as commands
+    if matches!(app.state, State::TextEdit) {
+        if !key.modifiers.intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) {
+            handle_text_edit(key.code, monitors, app, app_states);
+        }
+        return;
+    }
+    let Some(action) = app.keymap.action(key) else { return };
+    // other keys wait until the layout has been sent
+    if matches!(app.state, State::Applying) && !matches!(action, Action::Back | Action::Quit) {
+        return;
+    }
+    match action {
         // help
-        KeyCode::Char('?') => {
+        Action::Help => {
             if matches!(app.state, State::MonitorEdit | State::MonitorSwap | State::MenuSelect | State::InfoEdit) {
                 app.update_state(State::HelpPopup);
             }
         }
         // debug the command
-        KeyCode::Char('d') => {
+        Action::Debug => {
             if matches!(app.state, State::MonitorEdit | State::MonitorSwap | State::MenuSelect | State::InfoEdit) {
                 app.update_state(State::DebugPopup);
             }
         }
-        KeyCode::Char('q') => app.update_state(State::Quit),
-        KeyCode::Char('D') => {
+        // export the layout to the working directory
+        Action::Export => {
+            if matches!(app.state, State::MonitorEdit | State::MonitorSwap | State::MenuSelect | State::InfoEdit) {
+                app.export_error = export_layout(monitors).err().map(|err| err.kind());
+                app.update_state(State::ExportPopup);
+            }
+        }
+        Action::Quit => {
+            // stop the hooks and let the layout finish sending, then don't leave it unconfirmed
+            // - hooks cancelled for the apply are skipped for its revert too
+            let cancel = matches!(app.state, State::Applying);
+            if cancel {
+                app.hook_cancel.store(true, Ordering::Relaxed);
+                wait_for_apply(monitors, app);
+            }
+            if matches!(app.state, State::ConfirmApply) {
+                revert_apply(monitors, app, app_states);
+                app.hook_cancel.store(cancel, Ordering::Relaxed);
+                wait_for_apply(monitors, app);
+            }
+            app.update_state(State::Quit);
+        }
+        Action::Yes => match app.state {
+            State::ApplyPreview => apply_layout(monitors, app, app_states),
+            State::ConfirmApply => confirm_apply(monitors, app),
+            _ => {}
+        },
+        Action::No => match app.state {
+            State::ApplyPreview => app.update_state(app.previous_state),
+            State::ConfirmApply => revert_apply(monitors, app, app_states),
+            _ => {}
+        },
+        Action::Connections => {
             if matches!(app.state, State::MonitorEdit | State::MonitorSwap | State::MenuSelect | State::InfoEdit) {
                 app.update_state(State::ConnectionPopup);
             }
         }
-        // save: send to xrandr
-        KeyCode::Char('s') => send_to_xrandr(&monitors, *app),
-        KeyCode::Char('u') => {
+        // save: show what's changed, then send it to xrandr
+        Action::Apply => {
             if matches!(app.state, State::MonitorEdit | State::MonitorSwap | State::MenuSelect | State::InfoEdit) {
-                if let Some(last_state) = app_states.pop() {
-                    *monitors = last_state;
-
-                    // if empty, push a copy of the default state
-                    if app_states.len() == 0 {
-                        app_states.push((*monitors.clone()).to_vec());
-                    }
+                preview_apply(app);
+            } else if matches!(app.state, State::ApplyPreview) {
+                apply_layout(monitors, app, app_states);
+            }
+        }
+        // where monitors turned on from the connections popup go
+        Action::Attach => {
+            if matches!(app.state, State::ConnectionPopup) {
+                app.attach = match app.attach {
+                    AttachPoint::EndOfRow => AttachPoint::Beside(Dir::Right),
+                    AttachPoint::Beside(Dir::Right) => AttachPoint::Beside(Dir::Left),
+                    AttachPoint::Beside(Dir::Left) => AttachPoint::Beside(Dir::Down),
+                    AttachPoint::Beside(Dir::Down) => AttachPoint::Beside(Dir::Up),
+                    AttachPoint::Beside(Dir::Up) => AttachPoint::EndOfRow,
+                };
+            }
+        }
+        // undo and redo
+        Action::Undo | Action::Redo => {
+            if matches!(app.state, State::MonitorEdit | State::MonitorSwap | State::MenuSelect | State::InfoEdit) {
+                let layout = if action == Action::Undo { app_states.undo(monitors) } else { app_states.redo(monitors) };
+                if let Some(layout) = layout {
+                    replace_monitors(monitors, app, layout);
                 }
             }
         }
+        // browse the history
+        Action::History => {
+            if matches!(app.state, State::MonitorEdit | State::MonitorSwap | State::MenuSelect | State::InfoEdit) {
+                app.history_idx = app_states.position();
+                app.update_state(State::HistoryPopup);
+            }
+        }
+        // zoom and pan the canvas
+        Action::ZoomIn | Action::ZoomOut => {
+            if matches!(app.state, State::MonitorEdit | State::MonitorSwap | State::MenuSelect | State::InfoEdit) {
+                zoom_view(monitors, app, if action == Action::ZoomOut { ZOOM_STEP } else { 1.0 / ZOOM_STEP });
+            }
+        }
+        Action::PanLeft | Action::PanRight | Action::PanUp | Action::PanDown => {
+            if matches!(app.state, State::MonitorEdit | State::MonitorSwap | State::MenuSelect | State::InfoEdit) {
+                let direction = match action {
+                    Action::PanLeft => Dir::Left,
+                    Action::PanRight => Dir::Right,
+                    Action::PanUp => Dir::Up,
+                    _ => Dir::Down,
+                };
+                pan_view(monitors, app, direction);
+            }
+        }
+        Action::Fit => {
+            if matches!(app.state, State::MonitorEdit | State::MonitorSwap | State::MenuSelect | State::InfoEdit) {
+                fit_selection(monitors, app);
+            }
+        }
+        Action::FitAll => {
+            if matches!(app.state, State::MonitorEdit | State::MonitorSwap | State::MenuSelect | State::InfoEdit) {
+                app.view = None;
+            }
+        }
         // horizontal movement
-        KeyCode::Char('h') | KeyCode::Char('l') | KeyCode::Left | KeyCode::Right => {
-            let is_right = matches!(key, KeyCode::Char('l') | KeyCode::Right);
+        Action::Left | Action::Right => {
+            let is_right = action == Action::Right;
             let direction = if is_right { Dir::Right } else { Dir::Left };
 
             match app.state {
-                State::MonitorEdit => handle_monitor_edit(&mut app, &mut monitors, direction),
-                State::MonitorSwap => handle_monitor_swap(&mut app, &mut monitors, direction),
-                State::MenuSelect if matches!(app.menu_entry, MenuEntry::Scale) => handle_menu_scale(&mut app, &mut monitors, direction),
+                State::MonitorEdit => handle_monitor_edit(app, monitors, direction),
+                State::MonitorSwap => {
+                    let before = monitors.clone();
+                    let label = handle_monitor_swap(app, monitors, direction);
+                    if *monitors != before {
+                        app_states.push(&label, &before);
+                    }
+                }
+                State::MenuSelect if matches!(app.menu_entry, MenuEntry::Scale) => {
+                    app_states.push(&format!("scale {} on {}", if is_right { "up" } else { "down" }, monitors[app.selected_idx].name), monitors);
+                    handle_menu_scale(app, monitors, direction);
+                }
                 _ => {} // Unimplemented
             }
         }
 
         // vertical movement
-        KeyCode::Char('j') | KeyCode::Char('k') | KeyCode::Up | KeyCode::Down => {
-            let is_down = matches!(key, KeyCode::Char('j') | KeyCode::Down);
+        Action::Down | Action::Up => {
+            let is_down = action == Action::Down;
             let direction = if is_down { Dir::Down } else { Dir::Up };
 
             match app.state {
-                State::MonitorEdit      => handle_monitor_edit(&mut app, &mut monitors, direction),
-                State::MonitorSwap      => handle_monitor_swap(&mut app, &mut monitors, direction),
-                State::MenuSelect       => handle_menu_select(&mut app, is_down),
-                State::InfoEdit         => handle_info_edit(&mut app, &monitors, is_down),
-                State::ConnectionPopup  => handle_connection_edit(&mut app, monitors, is_down),
+                State::MonitorEdit      => handle_monitor_edit(app, monitors, direction),
+                State::MonitorSwap => {
+                    let before = monitors.clone();
+                    let label = handle_monitor_swap(app, monitors, direction);
+                    if *monitors != before {
+                        app_states.push(&label, &before);
+                    }
+                }
+                State::MenuSelect       => handle_menu_select(app, is_down),
+                State::InfoEdit         => handle_info_edit(app, monitors, is_down),
+                State::ConnectionPopup  => handle_connection_edit(app, monitors, is_down),
+                State::ResetPopup => {
+                    app.reset_target = if is_down { ResetTarget::Startup } else { ResetTarget::LastApplied };
+                }
+                State::HistoryPopup => {
+                    let last = app_states.undo.len() + app_states.redo.len();
+                    app.history_idx = if is_down { (app.history_idx + 1).min(last) } else { app.history_idx.saturating_sub(1) };
+                }
+                State::ConfirmApply | State::ApplyResult => {
+                    app.result_scroll = if is_down { app.result_scroll.saturating_add(1) } else { app.result_scroll.saturating_sub(1) };
+                }
                 _ => {} // Unimplemented
             }
         }
         // selection
-        KeyCode::Enter => {
+        Action::Select => {
             match app.state {
                 State::MonitorEdit => {
                     if monitors[app.current_idx].is_selected {
@@ -368,15 +939,46 @@ pub fn handle_key_press(key: KeyCode, mut monitors: &mut Monitors, mut app: &mut
                     }
                     app.update_state(app.previous_state);
                 }
-                State::MenuSelect => if matches!(app.menu_entry, MenuEntry::Framerate | MenuEntry::Resolution) { app.update_state(State::InfoEdit); },
+                State::MenuSelect => match app.menu_entry {
+                    MenuEntry::Framerate | MenuEntry::Resolution => app.update_state(State::InfoEdit),
+                    MenuEntry::Position | MenuEntry::Scale => start_text_edit(app, monitors),
+                    MenuEntry::Primary => toggle_primary(monitors, app.selected_idx, app_states),
+                    MenuEntry::Enabled => {
+                        let (idx, before) = (app.selected_idx, monitors.clone());
+                        toggle_enabled(app, monitors, idx);
+                        if *monitors != before {
+                            app_states.push(&format!("disable {}", monitors[idx].name), &before);
+                            // the menu was for a monitor that's gone
+                            monitors[idx].is_selected = false;
+                            app.menu_entry = MenuEntry::Position;
+                            app.update_state(State::MonitorEdit);
+                            app.focused_window = FocusedWindow::MonitorList;
+                        }
+                    }
+                    MenuEntry::Left | MenuEntry::Down | MenuEntry::Up | MenuEntry::Right
+                        if !neighbour_candidates(monitors, app.selected_idx).is_empty() => {
+                        app.extra_entry = 0;
+                        app.update_state(State::InfoEdit);
+                    }
+                    _ => {}
+                },
+                State::InfoEdit if menu_direction(app.menu_entry).is_some() => place_neighbour(monitors, app, app_states),
                 State::InfoEdit => {
                     assert!(matches!(app.menu_entry, MenuEntry::Framerate | MenuEntry::Resolution), "Editing something that's not Framerate or resolution!");
-                    app_states.push((*monitors.clone()).to_vec());
+                    let monitor = &monitors[app.selected_idx];
+                    let label = if matches!(app.menu_entry, MenuEntry::Resolution) {
+                        let res = monitor.sort_resolutions()[app.extra_entry];
+                        format!("resolution {}x{} on {}", res.0, res.1, monitor.name)
+                    } else {
+                        let framerates = monitor.available_resolutions.get(&monitor.resolution).expect("No available framerates");
+                        format!("framerate {}hz on {}", framerates[app.extra_entry], monitor.name)
+                    };
+                    app_states.push(&label, monitors);
                     if matches!(app.menu_entry, MenuEntry::Resolution) {
                         let old_res = monitors[app.selected_idx].displayed_resolution;
                         let new_res = monitors[app.selected_idx].sort_resolutions()[app.extra_entry];
                         let difference = (new_res.0 - old_res.0, new_res.1 - old_res.1);
-                        shift_res(&mut monitors, app.current_idx, difference);
+                        shift_res(monitors, app.current_idx, difference);
 
                         let updated_res = monitors[app.selected_idx].sort_resolutions()[app.extra_entry];
                         monitors[app.selected_idx].resolution = *updated_res;
@@ -388,15 +990,25 @@ pub fn handle_key_press(key: KeyCode, mut monitors: &mut Monitors, mut app: &mut
                         monitors[app.selected_idx].set_framerate(app.extra_entry);
                     }
                 }
-                State::DebugPopup | State::HelpPopup => app.update_state(app.previous_state),
-                State::ConnectionPopup => handle_monitor_connection_change(&mut app, &mut monitors),
+                State::DebugPopup | State::HelpPopup | State::ExportPopup | State::ApplyResult => app.update_state(app.previous_state),
+                State::ConnectionPopup => {
+                    let before = monitors.clone();
+                    toggle_enabled(app, monitors, app.connected_monitor_id);
+                    if *monitors != before {
+                        let monitor = &monitors[app.connected_monitor_id];
+                        app_states.push(&format!("{} {}", if monitor.is_enabled { "enable" } else { "disable" }, monitor.name), &before);
+                    }
+                }
+                State::ApplyPreview => apply_layout(monitors, app, app_states),
+                State::ConfirmApply => confirm_apply(monitors, app),
+                State::ResetPopup => reset_layout(monitors, app, app_states),
+                State::HistoryPopup => jump_to_history(monitors, app, app_states),
                 _ => {} //unimplemented
             }
         }
         // move
-        KeyCode::Char('m') => {
+        Action::Move => {
             if matches!(app.state, State::MonitorEdit | State::MenuSelect) {
-                app_states.push((*monitors.clone()).to_vec());
                 if matches!(app.state, State::MonitorEdit) {
                     monitors[app.selected_idx].is_selected = true;
                     app.current_idx = app.selected_idx;
@@ -405,19 +1017,25 @@ pub fn handle_key_press(key: KeyCode, mut monitors: &mut Monitors, mut app: &mut
                 app.focused_window = FocusedWindow::MonitorList;
             }
         }
+        // reset to a saved layout
+        Action::Reset => {
+            if matches!(app.state, State::MonitorEdit | State::MonitorSwap | State::MenuSelect | State::InfoEdit) {
+                app.reset_target = if app.applied_state.is_some() { ResetTarget::LastApplied } else { ResetTarget::Startup };
+                app.update_state(State::ResetPopup);
+            }
+        }
         // set primary
-        KeyCode::Char('p') => {
+        Action::Primary => {
             if matches!(app.state, State::MonitorEdit | State::MonitorSwap) {
-                app_states.push((*monitors.clone()).to_vec());
-                let mut iterator = monitors.iter_mut();
-                while let Some(element) = iterator.next() {
+                app_states.push(&format!("primary {}", monitors[app.selected_idx].name), monitors);
+                for element in monitors.iter_mut() {
                     element.is_primary = false;
                 }
                 monitors[app.selected_idx].is_primary = true;
             }
         }
         // Deselect
-        KeyCode::Esc => {
+        Action::Back => {
             match app.state{
                 State::MenuSelect => {
                     monitors[app.current_idx].is_selected = false;
@@ -431,19 +1049,122 @@ pub fn handle_key_press(key: KeyCode, mut monitors: &mut Monitors, mut app: &mut
                         app.focused_window = FocusedWindow::MonitorList;
                     }
                     app.update_state(app.previous_state);
-                    app_states.push((*monitors.clone()).to_vec());
                 }
                 State::InfoEdit => {
                     app.update_state(State::MenuSelect);
                 }
-                State::DebugPopup | State::HelpPopup | State::ConnectionPopup => app.update_state(app.previous_state),
+                State::DebugPopup | State::HelpPopup | State::ConnectionPopup | State::ExportPopup | State::ApplyPreview | State::ApplyResult | State::ResetPopup | State::HistoryPopup => app.update_state(app.previous_state),
+                State::ConfirmApply => revert_apply(monitors, app, app_states),
+                State::Applying => app.hook_cancel.store(true, Ordering::Relaxed),
                 _ => {}
             }
         }
+    }
+}
+
+// click a monitor to select it, and drag it somewhere else
+// - area is the whole terminal, and the monitor snaps to the edges of the others when it's dropped
+pub fn handle_mouse_event(mouse: MouseEvent, area: Rect, monitors: &mut Monitors, app: &mut App, app_states: &mut History) {
+    if !matches!(app.state, State::MonitorEdit) {
+        return;
+    }
+    // clicks outside the canvas would otherwise map to monitors beyond its edges, but a drag
+    // still has to be let go of wherever that happens
+    let canvas = monitor_area(area);
+    if app.drag.is_none() && !canvas.contains(Position::new(mouse.column, mouse.row)) {
+        return;
+    }
+    let transform = CanvasTransform::new(canvas, monitors, app);
+    let pixel = transform.to_pixel(mouse.column, mouse.row);
+
+    match mouse.kind {
+        MouseEventKind::Down(MouseButton::Left) => {
+            let Some(idx) = monitor_at(monitors, pixel) else { return };
+            app.selected_idx = idx;
+            app.current_idx = idx;
+            let position = monitors[idx].position;
+            app.drag = Some(Drag {
+                idx,
+                grab: (pixel.0 - position.0, pixel.1 - position.1),
+                bounds: view_bounds(monitors, app),
+                before: monitors.clone(),
+            });
+        }
+        MouseEventKind::Drag(MouseButton::Left) => {
+            if let Some(drag) = &app.drag {
+                monitors[drag.idx].position = (pixel.0 - drag.grab.0, pixel.1 - drag.grab.1);
+            }
+        }
+        MouseEventKind::Up(MouseButton::Left) => {
+            let Some(drag) = app.drag.take() else { return };
+            monitors[drag.idx].position = (pixel.0 - drag.grab.0, pixel.1 - drag.grab.1);
+            // a couple of cells either way, any finer than that is hard to hit with a mouse
+            let cell = transform.cell_size();
+            snap_to_edges(monitors, drag.idx, (cell.0 * 2, cell.1 * 2));
+            normalise_positions(monitors);
+            reset_proximity(monitors);
+            if *monitors != drag.before {
+                app_states.push(&format!("drag {}", monitors[drag.idx].name), &drag.before);
+            }
+            app.selected_idx = drag.idx;
+            app.current_idx = drag.idx;
+        }
         _ => {}
     }
 }
 
+// how far the canvas zooms and pans with each key press
+const ZOOM_STEP: f64 = 1.5;
+const PAN_STEP: f64 = 0.25;
+// the closest the canvas zooms in, in pixels across
+const MIN_VIEW: f64 = 200.0;
+
+// zoom in or out around the middle of the canvas, a factor over 1 zooms out
+// - zooming out stops once everything fits again
+fn zoom_view(monitors: &Monitors, app: &mut App, factor: f64) {
+    let (left, top, right, bottom) = view_bounds(monitors, app);
+    let centre = ((left + right) as f64 / 2.0, (top + bottom) as f64 / 2.0);
+    let half = (
+        ((right - left) as f64 * factor).max(MIN_VIEW) / 2.0,
+        ((bottom - top) as f64 * factor).max(MIN_VIEW) / 2.0,
+    );
+    let view = (
+        (centre.0 - half.0).round() as i32,
+        (centre.1 - half.1).round() as i32,
+        (centre.0 + half.0).round() as i32,
+        (centre.1 + half.1).round() as i32,
+    );
+
+    let layout = layout_bounds(monitors);
+    let covers_layout = view.0 <= layout.0 && view.1 <= layout.1 && view.2 >= layout.2 && view.3 >= layout.3;
+    app.view = if factor > 1.0 && covers_layout { None } else { Some(view) };
+}
+
+// move the canvas a quarter of the way across
+fn pan_view(monitors: &Monitors, app: &mut App, direction: Dir) {
+    let (left, top, right, bottom) = view_bounds(monitors, app);
+    let step = (((right - left) as f64 * PAN_STEP) as i32, ((bottom - top) as f64 * PAN_STEP) as i32);
+    let (dx, dy) = match direction {
+        Dir::Left => (-step.0, 0),
+        Dir::Right => (step.0, 0),
+        Dir::Up => (0, -step.1),
+        Dir::Down => (0, step.1),
+    };
+    app.view = Some((left + dx, top + dy, right + dx, bottom + dy));
+}
+
+// zoom to the selected monitor and the ones next to it
+fn fit_selection(monitors: &Monitors, app: &mut App) {
+    let monitor = &monitors[app.selected_idx];
+    let around: Monitors = std::iter::once(app.selected_idx)
+        .chain([monitor.left, monitor.right, monitor.up, monitor.down].into_iter().flatten())
+        .map(|i| monitors[i].clone())
+        .collect();
+    if around.iter().any(|m| m.is_enabled) {
+        app.view = Some(layout_bounds(&around));
+    }
+}
+
 // helper functions
 fn handle_monitor_edit(app: &mut App, monitors: &mut Monitors, direction: Dir) {
     if let Some(new_idx) = get_adjacent_monitor(monitors, app.selected_idx, direction) {
@@ -460,7 +1181,9 @@ fn handle_monitor_edit(app: &mut App, monitors: &mut Monitors, direction: Dir) {
     }
 }
 
-fn handle_monitor_swap(app: &mut App, monitors: &mut Monitors, direction: Dir) {
+// move the selected monitor, returning a description of what happened for the history
+fn handle_monitor_swap(app: &mut App, monitors: &mut Monitors, direction: Dir) -> String {
+    let label = format!("move {}", monitors[app.current_idx].name);
     let mut swap = false;
     let mut traverse = false;
 
@@ -472,23 +1195,26 @@ fn handle_monitor_swap(app: &mut App, monitors: &mut Monitors, direction: Dir) {
     }
 
     if swap {
+        let label = format!("swap {}↔{}", monitors[app.current_idx].name, monitors[app.selected_idx].name);
         app.extra_entry = 0;
         swap_monitors(monitors, app.current_idx, app.selected_idx, direction);
         app.current_idx = app.selected_idx;
+        return label;
     } else if !traverse {
         match direction {
             Dir::Left | Dir::Right => {
                 if let Some((pivot_monitor, vert_direction)) = find_vertical_pivot(monitors, app.selected_idx, direction) {
-                    horizontal_push(monitors, pivot_monitor, vert_direction, direction, *app);
+                    horizontal_push(monitors, pivot_monitor, vert_direction, direction, app);
                 }
             }
             Dir::Up | Dir::Down => {
                 if let Some((pivot_monitor, vert_direction)) = find_horizontal_pivot(monitors, app.selected_idx, direction) {
-                    vert_push(monitors, pivot_monitor, vert_direction, direction, *app);
+                    vert_push(monitors, pivot_monitor, vert_direction, direction, app);
                 }
             }
         }
     }
+    label
 }
 
 fn handle_menu_scale(app: &mut App, monitors: &mut Monitors, direction: Dir) {
@@ -515,7 +1241,9 @@ fn handle_menu_select(app: &mut App, is_down: bool) {
 }
 
 fn handle_info_edit(app: &mut App, monitors: &Monitors, is_down: bool) {
-    let max_length = if app.menu_entry == MenuEntry::Framerate {
+    let max_length = if menu_direction(app.menu_entry).is_some() {
+        neighbour_candidates(monitors, app.selected_idx).len().saturating_sub(1)
+    } else if app.menu_entry == MenuEntry::Framerate {
         monitors[app.selected_idx]
             .available_resolutions
             .get(&monitors[app.selected_idx].resolution)
@@ -535,6 +1263,128 @@ fn handle_info_edit(app: &mut App, monitors: &Monitors, is_down: bool) {
     }
 }
 
+// which side of the selected monitor a neighbour menu entry is for
+fn menu_direction(entry: MenuEntry) -> Option<Dir> {
+    match entry {
+        MenuEntry::Left => Some(Dir::Left),
+        MenuEntry::Down => Some(Dir::Down),
+        MenuEntry::Up => Some(Dir::Up),
+        MenuEntry::Right => Some(Dir::Right),
+        _ => None,
+    }
+}
+
+// monitors that can be put next to the selected one
+pub fn neighbour_candidates(monitors: &Monitors, idx: usize) -> Vec<usize> {
+    (0..monitors.len()).filter(|&i| i != idx && monitors[i].is_enabled).collect()
+}
+
+// move the monitor picked from the list to the side of the selected monitor the menu entry is for
+// - lined up with the top or left edge, pushing anything already on that side along
+fn place_neighbour(monitors: &mut Monitors, app: &App, app_states: &mut History) {
+    let Some(direction) = menu_direction(app.menu_entry) else { return };
+    let Some(&other_idx) = neighbour_candidates(monitors, app.selected_idx).get(app.extra_entry) else { return };
+    let selected = &monitors[app.selected_idx];
+    let current = match direction {
+        Dir::Left => selected.left,
+        Dir::Down => selected.down,
+        Dir::Up => selected.up,
+        Dir::Right => selected.right,
+    };
+    if current == Some(other_idx) {
+        return;
+    }
+
+    let side = match direction {
+        Dir::Left => "left of",
+        Dir::Down => "below",
+        Dir::Up => "above",
+        Dir::Right => "right of",
+    };
+    app_states.push(&format!("place {} {} {}", monitors[other_idx].name, side, selected.name), monitors);
+    place_monitor(monitors, other_idx, app.selected_idx, direction, Align::Start);
+}
+
+// start typing a position or scale, from what it is now
+fn start_text_edit(app: &mut App, monitors: &Monitors) {
+    let monitor = &monitors[app.selected_idx];
+    app.text_input = match app.menu_entry {
+        MenuEntry::Position => format!("{},{}", monitor.position.0, monitor.position.1),
+        _ => format!("{:.2}", monitor.scale),
+    };
+    app.input_error = None;
+    app.update_state(State::TextEdit);
+}
+
+fn handle_text_edit(key: KeyCode, monitors: &mut Monitors, app: &mut App, app_states: &mut History) {
+    match key {
+        KeyCode::Char(c) => {
+            app.text_input.push(c);
+            app.input_error = None;
+        }
+        KeyCode::Backspace => {
+            app.text_input.pop();
+            app.input_error = None;
+        }
+        KeyCode::Enter => match set_typed_value(monitors, app, app_states) {
+            Ok(()) => app.update_state(State::MenuSelect),
+            Err(err) => app.input_error = Some(err),
+        },
+        KeyCode::Esc => app.update_state(State::MenuSelect),
+        _ => {}
+    }
+}
+
+// a position typed as X,Y or XxY
+pub fn parse_position(text: &str) -> Result<(i32, i32), String> {
+    let invalid = || format!("'{}' isn't a position, type it as X,Y", text);
+    let (x, y) = text.split_once([',', 'x']).ok_or_else(invalid)?;
+    let position: (i32, i32) = (x.trim().parse().map_err(|_| invalid())?, y.trim().parse().map_err(|_| invalid())?);
+    if position.0 < 0 || position.1 < 0 {
+        return Err(format!("{},{} is off the screen, positions start at 0,0", position.0, position.1));
+    }
+    Ok(position)
+}
+
+// the smallest and largest scale that can be typed, past these the text gets unreadable
+const MIN_SCALE: f32 = 0.25;
+const MAX_SCALE: f32 = 4.0;
+
+pub fn parse_scale(text: &str) -> Result<f32, String> {
+    let scale: f32 = text.trim().parse().map_err(|_| format!("'{}' isn't a number", text))?;
+    if !(MIN_SCALE..=MAX_SCALE).contains(&scale) {
+        return Err(format!("scale has to be between {} and {}", MIN_SCALE, MAX_SCALE));
+    }
+    Ok(scale)
+}
+
+// set the selected monitor's position or scale to what was typed, as one step in the history
+fn set_typed_value(monitors: &mut Monitors, app: &App, app_states: &mut History) -> Result<(), String> {
+    let idx = app.selected_idx;
+    match app.menu_entry {
+        MenuEntry::Position => {
+            let position = parse_position(&app.text_input)?;
+            if position != monitors[idx].position {
+                app_states.push(&format!("position {},{} on {}", position.0, position.1, monitors[idx].name), monitors);
+                monitors[idx].position = position;
+            }
+        }
+        _ => {
+            let scale = parse_scale(&app.text_input)?;
+            if scale != monitors[idx].scale {
+                app_states.push(&format!("scale {:.2} on {}", scale, monitors[idx].name), monitors);
+                monitors[idx].scale = scale;
+                let difference = monitors[idx].get_res_difference();
+                shift_res(monitors, idx, difference);
+                monitors[idx].update_scale();
+            }
+        }
+    }
+    normalise_positions(monitors);
+    reset_proximity(monitors);
+    Ok(())
+}
+
 fn find_horizontal_pivot(monitors: &Monitors, idx: usize, direction: Dir) -> Option<(usize, Dir)> {
     if let Some(left) = monitors[idx].left {
         if direction == Dir::Up && monitors[left].up.is_none()
@@ -571,118 +1421,216 @@ fn find_vertical_pivot(monitors: &Monitors, idx: usize, direction: Dir) -> Optio
     None
 }
 
-fn send_to_xrandr(monitors: &Monitors, app: App) {
-    if !app.debug && matches!(app.state, State::MonitorEdit | State::MonitorSwap | State::MenuSelect | State::InfoEdit) {
-        let args = convert_monitors_to_args(monitors, false);
-
-        // TODO: display this in a popup
-        let output = Command::new("xrandr")
-            .args(args)
-            .output()
-            .expect("failed to execute process");
-
-        println!("{:?}", output);
-    }
-}
-
-fn convert_monitors_to_args(monitors: &Monitors, debug: bool) -> Vec<String> {
-    let mut args: Vec<String> = Vec::new();
-    let mut iterator = monitors.iter();
-    while let Some(element) = iterator.next() {
-        if !element.is_enabled { continue; }
-        if debug { args.push("\n> ".to_string()); }
-        args.push("--output".to_string());
-        args.push(element.name.to_string());
-        if element.is_primary { args.push("--primary".to_string()); }
-        args.push("--mode".to_string());
-        args.push(format!("{}x{}", element.resolution.0, element.resolution.1));
-        args.push("--rate".to_string());
-        args.push(element.framerate.to_string());
-        args.push("--pos".to_string());
-        args.push(format!("{}x{}", element.position.0, element.position.1));
-        args.push("--scale".to_string());
-        args.push(format!("{:.2}", 1.0/element.scale));
-    };
+// show the changes before applying them
+fn preview_apply(app: &mut App) {
+    // the layout gets printed on the way out instead
+    if app.print_json {
+        app.update_state(State::Quit);
+        return;
+    }
+    app.update_state(State::ApplyPreview);
+}
 
-    return args;
+// the layout on the display, the last one confirmed or what we started with
+fn displayed_layout(app: &App, app_states: &History) -> Option<Monitors> {
+    app.applied_state.clone().or_else(|| startup_layout(app, app_states))
 }
 
-fn handle_monitor_connection_change(app: &mut App, monitors: &mut Monitors) {
-    if monitors[app.connected_monitor_id].is_enabled {
-        monitors[app.connected_monitor_id].is_enabled = false;
-        // disable connected monitor
-        if app.selected_idx == app.connected_monitor_id || app.current_idx == app.connected_monitor_id {
-            let mut selected_idx = 0;
-            let mut enabled_mon = false;
-            while !enabled_mon && selected_idx < monitors.len() {
-                if monitors[selected_idx].is_enabled {
-                    enabled_mon = true;
-                } else {
-                    selected_idx += 1;
-                }
-            }
-            if !enabled_mon {
-                // don't let them disable the last monitor
-                monitors[app.connected_monitor_id].is_enabled = true;
-                return;
-            }
-            app.selected_idx = selected_idx;
-            app.current_idx = selected_idx;
+// only the outputs that differ from what's on the display, or everything if we don't know
+fn apply_args(monitors: &Monitors, app: &App, app_states: &History) -> Vec<String> {
+    diff_args(&displayed_layout(app, app_states).unwrap_or_default(), monitors)
+}
+
+// apply the previewed layout, and start the countdown to put the previous one back
+fn apply_layout(monitors: &mut Monitors, app: &mut App, app_states: &History) {
+    // back to where the preview was opened from, so confirming returns there
+    app.update_state(app.previous_state);
+
+    let args = apply_args(monitors, app, app_states);
+    if args.is_empty() {
+        return;
+    }
+    let displayed = displayed_layout(app, app_states).unwrap_or_default();
+    let snapshot = if displayed.is_empty() { monitors.clone() } else { displayed.clone() };
+
+    send_to_xrandr(&displayed, monitors, app, Some(snapshot));
+}
+
+fn confirm_apply(monitors: &Monitors, app: &mut App) {
+    app.applied_state = Some(monitors.clone());
+    app.revert_state = None;
+    app.revert_deadline = None;
+    app.update_state(app.previous_state);
+}
+
+fn revert_apply(monitors: &mut Monitors, app: &mut App, app_states: &mut History) {
+    app.revert_deadline = None;
+    app.update_state(app.previous_state);
+    if let Some(snapshot) = app.revert_state.take() {
+        app_states.push("revert unconfirmed layout", monitors);
+        let unconfirmed = monitors.clone();
+        replace_monitors(monitors, app, snapshot);
+        if diff_args(&unconfirmed, monitors).is_empty() {
+            return;
+        }
+        send_to_xrandr(&unconfirmed, monitors, app, None);
+    }
+}
+
+// once the apply running in the background is done, ask to keep the layout, or show why it failed
+// - a revert that worked just goes back to where it was started from
+pub fn check_apply_done(monitors: &mut Monitors, app: &mut App) {
+    let Some((pending, snapshot)) = app.applying.take() else { return };
+    let Some(result) = pending.lock().unwrap().take() else {
+        app.applying = Some((pending, snapshot));
+        return;
+    };
+
+    record_step(app, |dir| record_command(dir, &result.command));
+    app.update_state(app.previous_state);
+    app.result_scroll = 0;
+    if result.success {
+        requery_monitors(monitors, app);
+        if let Some(snapshot) = snapshot {
+            // nothing changed in a dry run, so there's nothing to count down to
+            app.revert_state = Some(snapshot);
+            app.revert_deadline = (!app.dry_run).then(|| Instant::now() + REVERT_TIMEOUT);
+            app.update_state(State::ConfirmApply);
+            app.apply_result = Some(result);
         }
+    } else {
+        app.update_state(State::ApplyResult);
+        app.apply_result = Some(result);
+    }
+}
 
-        if let Some(right_idx) = monitors[app.connected_monitor_id].right {
-            monitors[right_idx].left = None;
-            let difference = monitors[right_idx].position.0 - monitors[app.connected_monitor_id].position.0;
-            shift_mons(monitors, right_idx, difference, false, Vec::new());
-        } else if let Some(down_idx) = monitors[app.connected_monitor_id].down {
-            let difference = monitors[down_idx].position.1 - monitors[app.connected_monitor_id].position.1;
-            monitors[down_idx].up = None;
-            shift_mons(monitors, down_idx, difference, true, Vec::new());
+// block until the apply running in the background is done, for replays and scripts
+pub fn wait_for_apply(monitors: &mut Monitors, app: &mut App) {
+    while app.applying.is_some() {
+        check_apply_done(monitors, app);
+        if app.applying.is_some() {
+            thread::sleep(Duration::from_millis(10));
         }
+    }
+}
 
-        monitors[app.connected_monitor_id].position = (-1,-1);
-        monitors[app.connected_monitor_id].resolution = (0,0);
+// revert if the countdown ran out before the layout was confirmed
+pub fn check_revert_timeout(monitors: &mut Monitors, app: &mut App, app_states: &mut History) {
+    if matches!(app.state, State::ConfirmApply) && app.revert_deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+        revert_apply(monitors, app, app_states);
+    }
+}
 
-        // purge this monitor from existence
-        if let Some(idx) = monitors[app.connected_monitor_id].right { monitors[idx].left = None }
-        if let Some(idx) = monitors[app.connected_monitor_id].left { monitors[idx].right = None }
-        if let Some(idx) = monitors[app.connected_monitor_id].down { monitors[idx].up = None }
-        if let Some(idx) = monitors[app.connected_monitor_id].up { monitors[idx].down = None }
+// send what changed between two layouts, a stage at a time if xrandr won't take it in one go
+// - the hooks and xrandr run in the background, check_apply_done picks up the result
+fn send_to_xrandr(before: &Monitors, after: &Monitors, app: &mut App, snapshot: Option<Monitors>) {
+    app.hook_log = HookLog::default();
+    app.hook_cancel = HookCancel::default();
+    app.apply_result = None;
+    let profile = if app.hooks.is_empty() { None } else {
+        find_matching_profile(&profiles_dir(), after).ok().flatten().map(|profile| profile.name)
+    };
 
-        monitor_proximity(monitors);
+    let pending = apply_with_hooks(app.backend(), &app.hooks, profile, before, after, &app.hook_log, &app.hook_cancel);
+    app.applying = Some((pending, snapshot));
+    app.update_state(State::Applying);
+}
 
-        update_neighbor_positions(monitors);
-    } else {
-        // connect disabled monitor
-        monitors[app.connected_monitor_id].is_enabled = true;
-
-        //find rightmost monitor on first row, and connect it there
-        let right_idx = find_rightmost_monitor(monitors, app.current_idx);
-        let new_position = (
-            monitors[right_idx].position.0 + monitors[right_idx].resolution.0,
-            monitors[right_idx].position.1
-        );
-        monitors[app.connected_monitor_id].position = new_position;
-        let _resolutions = &monitors[app.connected_monitor_id].available_resolutions;
-        let _sorted_resolutions = monitors[app.connected_monitor_id].sort_resolutions();
-        let new_res = monitors[app.connected_monitor_id].sort_resolutions()[0];
-        monitors[app.connected_monitor_id].resolution = *new_res;
-        monitors[app.connected_monitor_id].scale = 1.0;
-        monitors[app.connected_monitor_id].update_scale();
-        monitor_proximity(monitors);
-    }
-}
-
-fn find_rightmost_monitor(monitors: &Monitors, idx: usize) -> usize {
-    if let Some(right_idx) = monitors[idx].right {
-        return find_rightmost_monitor(monitors, right_idx);
+// read the layout back so what's on screen matches what xrandr actually did
+// - the mock or saved monitors never change, and a dry run changes nothing, so there's nothing to
+//   read back then
+fn requery_monitors(monitors: &mut Monitors, app: &mut App) {
+    if app.debug || app.dry_run || app.input.is_some() { return; }
+    if let Ok(queried) = app.backend().query() {
+        replace_monitors(monitors, app, queried);
+    }
+}
+
+// swap in a different layout, keeping the same monitor selected where we can
+fn replace_monitors(monitors: &mut Monitors, app: &mut App, layout: Monitors) {
+    let selected_name = monitors[app.selected_idx].name.clone();
+    let is_selected = monitors[app.current_idx].is_selected;
+
+    *monitors = layout;
+    for monitor in monitors.iter_mut() {
+        monitor.is_selected = false;
+    }
+    app.selected_idx = monitors.iter().position(|m| m.name == selected_name && m.is_enabled)
+        .or_else(|| monitors.iter().position(|m| m.is_enabled))
+        .unwrap_or(0);
+    app.current_idx = app.selected_idx;
+    monitors[app.current_idx].is_selected = is_selected;
+}
+
+// the first layout we saw, falling back to the start of the history
+fn startup_layout(app: &App, app_states: &History) -> Option<Monitors> {
+    app.startup_state.clone().or_else(|| app_states.first().cloned())
+}
+
+// go back to the last applied layout or the one we started with, undoable like any other change
+fn reset_layout(monitors: &mut Monitors, app: &mut App, app_states: &mut History) {
+    let (layout, label) = match app.reset_target {
+        ResetTarget::LastApplied => (app.applied_state.clone(), "reset to last applied layout"),
+        ResetTarget::Startup => (startup_layout(app, app_states), "reset to startup layout"),
+    };
+    let Some(layout) = layout else { return };
+
+    app_states.push(label, monitors);
+    replace_monitors(monitors, app, layout);
+    monitors[app.current_idx].is_selected = false;
+    app.focused_window = FocusedWindow::MonitorList;
+    app.update_state(State::MonitorEdit);
+}
+
+// go to the layout highlighted in the history popup, the rest of the history stays available
+fn jump_to_history(monitors: &mut Monitors, app: &mut App, app_states: &mut History) {
+    if let Some(layout) = app_states.jump(monitors, app.history_idx) {
+        replace_monitors(monitors, app, layout);
+    }
+    monitors[app.current_idx].is_selected = false;
+    app.focused_window = FocusedWindow::MonitorList;
+    app.update_state(State::MonitorEdit);
+}
+
+fn export_layout(monitors: &Monitors) -> io::Result<()> {
+    write_shell_script(monitors, Path::new(SCRIPT_FILE))?;
+    write_xorg_conf(monitors, Path::new(XORG_CONF_FILE))
+}
+
+// make a monitor the primary one, or leave no primary if it already was
+fn toggle_primary(monitors: &mut Monitors, idx: usize, app_states: &mut History) {
+    if monitors[idx].is_primary {
+        app_states.push(&format!("no primary, was {}", monitors[idx].name), monitors);
+        monitors[idx].is_primary = false;
+        return;
+    }
+    app_states.push(&format!("primary {}", monitors[idx].name), monitors);
+    for element in monitors.iter_mut() {
+        element.is_primary = false;
+    }
+    monitors[idx].is_primary = true;
+}
+
+// turn a monitor off, moving its neighbours in to fill the gap, or back on at the end of the row
+fn toggle_enabled(app: &mut App, monitors: &mut Monitors, idx: usize) {
+    if monitors[idx].is_enabled {
+        // don't let them disable the last monitor
+        if !disable_monitor(monitors, idx) {
+            return;
+        }
+        if app.selected_idx == idx || app.current_idx == idx {
+            let selected_idx = monitors.iter().position(|m| m.is_enabled).unwrap_or(0);
+            app.selected_idx = selected_idx;
+            app.current_idx = selected_idx;
+        }
     } else {
-        return idx;
+        let anchor_idx = if app.attach == AttachPoint::EndOfRow { app.current_idx } else { app.selected_idx };
+        attach_monitor(monitors, idx, anchor_idx, app.attach);
     }
 }
 
 fn handle_connection_edit(app: &mut App, monitors: &Monitors, is_down: bool) {
-    if is_down && app.connected_monitor_id < monitors.len() {
+    if is_down && app.connected_monitor_id + 1 < monitors.len() {
         app.connected_monitor_id += 1;
     } else if !is_down && app.connected_monitor_id > 0 {
         app.connected_monitor_id -= 1;
@@ -692,8 +1640,8 @@ fn handle_connection_edit(app: &mut App, monitors: &Monitors, is_down: bool) {
 // Generate the Line to draw extra information (e.g. framerate)
 fn generate_extra_info(
     monitors: &Monitors,
-    app: App,
-) -> Vec<Line> {
+    app: &App,
+) -> Vec<Line<'static>> {
     if let Some(monitor) = monitors.get(app.selected_idx) {
         if app.menu_entry == MenuEntry::Framerate {
             if let Some(framerates) = monitor.available_resolutions.get(&monitor.resolution) {
@@ -702,11 +1650,9 @@ fn generate_extra_info(
                     .enumerate()
                     .map(|(i, fr)| {
                         let mut framerate_style = Style::default();
-                        if app.extra_entry == i {
-                            if matches!(app.state, State::InfoEdit) {
-                                framerate_style.fg = Some(Color::Yellow);
-                                framerate_style = framerate_style.add_modifier(Modifier::BOLD);
-                            }
+                        if app.extra_entry == i && matches!(app.state, State::InfoEdit) {
+                            framerate_style.fg = Some(Color::Yellow);
+                            framerate_style = framerate_style.add_modifier(Modifier::BOLD);
                         }
                         if monitors[app.selected_idx].framerate == *fr {
                             framerate_style = framerate_style.add_modifier(Modifier::UNDERLINED);
@@ -730,10 +1676,8 @@ fn generate_extra_info(
                     .enumerate()
                     .map(|(i, res)| {
                         let mut resolution_style = Style::default().add_modifier(Modifier::BOLD);
-                        if app.extra_entry == i {
-                            if matches!(app.state, State::InfoEdit) {
-                                resolution_style.fg = Some(Color::Yellow);
-                            }
+                        if app.extra_entry == i && matches!(app.state, State::InfoEdit) {
+                            resolution_style.fg = Some(Color::Yellow);
                         }
                         if monitors[app.selected_idx].resolution == (res.0, res.1) {
                             resolution_style = resolution_style.add_modifier(Modifier::UNDERLINED);
@@ -750,6 +1694,31 @@ fn generate_extra_info(
             } else {
                 vec![Line::from("No available resolutions")]
             }
+        } else if let Some(direction) = menu_direction(app.menu_entry) {
+            let current = match direction {
+                Dir::Left => monitor.left,
+                Dir::Down => monitor.down,
+                Dir::Up => monitor.up,
+                Dir::Right => monitor.right,
+            };
+            let candidates = neighbour_candidates(monitors, app.selected_idx);
+            if candidates.is_empty() {
+                return vec![Line::from("No other monitors enabled")];
+            }
+            candidates
+                .iter()
+                .enumerate()
+                .map(|(i, &idx)| {
+                    let mut neighbour_style = Style::default().add_modifier(Modifier::BOLD);
+                    if app.extra_entry == i && matches!(app.state, State::InfoEdit) {
+                        neighbour_style.fg = Some(Color::Yellow);
+                    }
+                    if current == Some(idx) {
+                        neighbour_style = neighbour_style.add_modifier(Modifier::UNDERLINED);
+                    }
+                    Line::from(Span::styled(format!("Option {}: {}", i, monitors[idx].display_name()), neighbour_style))
+                })
+                .collect()
         } else {
             vec![Line::from("Nothing to see here!")]
         }
@@ -761,14 +1730,14 @@ fn generate_extra_info(
 // Generate the Line from monitor info
 fn generate_monitor_info(
     monitors: &Monitors,
-    app: App
-) -> Vec<Line> {
-    fn get_style(app: App, entry: MenuEntry) -> Style {
+    app: &App
+) -> Vec<Line<'static>> {
+    fn get_style(app: &App, entry: MenuEntry) -> Style {
         if app.menu_entry == entry {
             Style::default()
                 .add_modifier(Modifier::BOLD)
                 .fg(match app.state {
-                    State::InfoEdit => Color::LightMagenta,
+                    State::InfoEdit | State::TextEdit => Color::LightMagenta,
                     State::MenuSelect => Color::Yellow,
                     _ => Color::White,
                 })
@@ -778,21 +1747,24 @@ fn generate_monitor_info(
     }
 
     // underline value if it can be modified from this menu
-    fn format_monitor_info(label: &str, value: String, style: Style, indicate_modifiable: bool) -> Line {
+    fn format_monitor_info(label: &str, value: String, style: Style, indicate_modifiable: bool) -> Line<'static> {
         if indicate_modifiable {
-            Line::from(vec![Span::styled(format!("{label}: "), style), Span::styled(format!("{value}"), style.add_modifier(Modifier::UNDERLINED))])
+            Line::from(vec![Span::styled(format!("{label}: "), style), Span::styled(value, style.add_modifier(Modifier::UNDERLINED))])
         } else {
             Line::from(vec![Span::styled(format!("{label}: {value}"), style)])
         }
     }
 
+    // what's been typed so far, with a cursor
+    let typing = |entry: MenuEntry| (app.state == State::TextEdit && app.menu_entry == entry).then(|| format!("{}▏", app.text_input));
+
     if let Some(monitor) = monitors.get(app.selected_idx) {
-        vec![
+        let mut info = vec![
             format_monitor_info(
                 "Position",
-                format!("({}, {})", monitor.position.0, monitor.position.1),
+                typing(MenuEntry::Position).unwrap_or(format!("({}, {})", monitor.position.0, monitor.position.1)),
                 get_style(app, MenuEntry::Position),
-                false,
+                matches!(app.menu_entry, MenuEntry::Position),
             ),
             format_monitor_info(
                 "Resolution",
@@ -808,7 +1780,9 @@ fn generate_monitor_info(
             ),
             format_monitor_info(
                 "Scale",
-                if matches!(app.menu_entry, MenuEntry::Scale) && matches!(app.state, State::MenuSelect) {
+                if let Some(typed) = typing(MenuEntry::Scale) {
+                    typed
+                } else if matches!(app.menu_entry, MenuEntry::Scale) && matches!(app.state, State::MenuSelect) {
                     format!("< {:.2} >", monitor.resolution.0 as f32 / monitor.displayed_resolution.0 as f32)
                 } else {
                     format!("{:.2}", monitor.resolution.0 as f32 / monitor.displayed_resolution.0 as f32)
@@ -820,31 +1794,37 @@ fn generate_monitor_info(
                 "Primary",
                 if monitor.is_primary { "Yes".to_string() } else { "No".to_string() },
                 get_style(app, MenuEntry::Primary),
-                false,
+                matches!(app.menu_entry, MenuEntry::Primary),
+            ),
+            format_monitor_info(
+                "Enabled",
+                if monitor.is_enabled { "Yes".to_string() } else { "No".to_string() },
+                get_style(app, MenuEntry::Enabled),
+                matches!(app.menu_entry, MenuEntry::Enabled),
             ),
             format_monitor_info(
                 "Left",
                 monitor.left.map_or("None".to_string(), |idx| monitors[idx].name.clone()),
                 get_style(app, MenuEntry::Left),
-                false,
+                matches!(app.menu_entry, MenuEntry::Left),
             ),
             format_monitor_info(
                 "Down",
                 monitor.down.map_or("None".to_string(), |idx| monitors[idx].name.clone()),
                 get_style(app, MenuEntry::Down),
-                false,
+                matches!(app.menu_entry, MenuEntry::Down),
             ),
             format_monitor_info(
                 "Up",
                 monitor.up.map_or("None".to_string(), |idx| monitors[idx].name.clone()),
                 get_style(app, MenuEntry::Up),
-                false,
+                matches!(app.menu_entry, MenuEntry::Up),
             ),
             format_monitor_info(
                 "Right",
                 monitor.right.map_or("None".to_string(), |idx| monitors[idx].name.clone()),
                 get_style(app, MenuEntry::Right),
-                false,
+                matches!(app.menu_entry, MenuEntry::Right),
             ),
             format_monitor_info(
                 "Resolutions",
@@ -859,7 +1839,14 @@ fn generate_monitor_info(
                 get_style(app, MenuEntry::Resolutions),
                 false,
             ),
-        ]
+        ];
+        if let (State::TextEdit, Some(err)) = (app.state, &app.input_error) {
+            info.insert(0, Line::from(Span::styled(err.clone(), Style::default().fg(Color::Red))));
+        }
+        if let Some(err) = &app.record_error {
+            info.insert(0, Line::from(Span::styled(err.clone(), Style::default().fg(Color::Red))));
+        }
+        info
     } else {
         vec![Line::from("No monitor selected")]
     }
@@ -894,28 +1881,137 @@ fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
     horizontal_layout[1]
 }
 
-// draw monitors as defined
-fn draw_monitors(f: &mut ratatui::Frame, area: Rect, monitors: &[Monitor], app: App) {
-    let total_width: f64 = monitors.iter().map(|m| m.position.0 + m.displayed_resolution.0 as i32).max().unwrap_or(0).into();
-    let total_height: f64 = monitors.iter().map(|m| m.position.1 + m.displayed_resolution.1 as i32).max().unwrap_or(0).into();
+// how much of the canvas the layout takes up, the rest is margin
+const CANVAS_FILL: f64 = 0.9;
+// terminal cells are about twice as tall as they are wide
+const CELL_ASPECT: f64 = 2.0;
+
+// how pixel positions are drawn on the canvas, and how terminal cells map back to pixels for the mouse
+// - the canvas counts in cell widths both ways, so one scale keeps monitors in proportion
+#[derive(Debug, Clone, Copy)]
+pub struct CanvasTransform {
+    pub area: Rect,
+    pub scale: f64,                     // canvas units per pixel
+    pub origin: (i32, i32),             // top left of the layout
+    pub offset: (f64, f64),             // from the top left of the canvas, centring the layout
+}
+
+// the smallest box around every enabled monitor, as left, top, right, bottom
+pub fn layout_bounds(monitors: &[Monitor]) -> (i32, i32, i32, i32) {
+    let enabled = || monitors.iter().filter(|m| m.is_enabled);
+    (
+        enabled().map(|m| m.position.0).min().unwrap_or(0),
+        enabled().map(|m| m.position.1).min().unwrap_or(0),
+        enabled().map(|m| m.position.0 + m.displayed_resolution.0).max().unwrap_or(1),
+        enabled().map(|m| m.position.1 + m.displayed_resolution.1).max().unwrap_or(1),
+    )
+}
+
+// the part of the layout the canvas shows
+// - bounds hold still while a monitor is dragged, otherwise it would chase the cursor
+pub fn view_bounds(monitors: &[Monitor], app: &App) -> (i32, i32, i32, i32) {
+    match &app.drag {
+        Some(drag) => drag.bounds,
+        None => app.view.unwrap_or_else(|| layout_bounds(monitors)),
+    }
+}
+
+impl CanvasTransform {
+    pub fn new(area: Rect, monitors: &[Monitor], app: &App) -> CanvasTransform {
+        CanvasTransform::fit(area, view_bounds(monitors, app))
+    }
+
+    // fit some bounds on the canvas, centred
+    pub fn fit(area: Rect, bounds: (i32, i32, i32, i32)) -> CanvasTransform {
+        let (left, top, right, bottom) = bounds;
+        let (layout_width, layout_height) = ((right - left).max(1) as f64, (bottom - top).max(1) as f64);
+        let (canvas_width, canvas_height) = Self::canvas_size(area);
+
+        let scale = (canvas_width * CANVAS_FILL / layout_width).min(canvas_height * CANVAS_FILL / layout_height);
+        let offset = ((canvas_width - layout_width * scale) / 2.0, (canvas_height - layout_height * scale) / 2.0);
+        CanvasTransform { area, scale, origin: (left, top), offset }
+    }
+
+    fn canvas_size(area: Rect) -> (f64, f64) {
+        (area.width as f64, area.height as f64 * CELL_ASPECT)
+    }
+
+    pub fn bounds(&self) -> (f64, f64) {
+        Self::canvas_size(self.area)
+    }
 
-    let scale_x = 0.9;
-    let scale_y = 0.5;
+    // top left corner of a monitor on the canvas, which counts y upwards
+    pub fn to_canvas(&self, position: (i32, i32)) -> (f64, f64) {
+        let x = self.offset.0 + (position.0 - self.origin.0) as f64 * self.scale;
+        let y = self.bounds().1 - (self.offset.1 + (position.1 - self.origin.1) as f64 * self.scale);
+        (x, y)
+    }
+
+    // size of a monitor on the canvas, in cell widths
+    pub fn to_canvas_size(&self, resolution: (i32, i32)) -> (f64, f64) {
+        (resolution.0 as f64 * self.scale, resolution.1 as f64 * self.scale)
+    }
+
+    // the pixel under the middle of a terminal cell
+    pub fn to_pixel(&self, column: u16, row: u16) -> (i32, i32) {
+        let canvas_x = column.saturating_sub(self.area.x) as f64 + 0.5;
+        let canvas_y = (row.saturating_sub(self.area.y) as f64 + 0.5) * CELL_ASPECT;
+        let x = (canvas_x - self.offset.0) / self.scale + self.origin.0 as f64;
+        let y = (canvas_y - self.offset.1) / self.scale + self.origin.1 as f64;
+        (x.round() as i32, y.round() as i32)
+    }
+
+    // how many pixels a terminal cell covers
+    pub fn cell_size(&self) -> (i32, i32) {
+        ((1.0 / self.scale).ceil() as i32, (CELL_ASPECT / self.scale).ceil() as i32)
+    }
+}
+
+// cut a label down to a number of characters, marking where it was cut
+pub fn fit_label(label: &str, width: usize) -> String {
+    if label.chars().count() <= width {
+        return label.to_string();
+    }
+    match width {
+        0 => String::new(),
+        _ => label.chars().take(width - 1).chain(std::iter::once('…')).collect(),
+    }
+}
+
+// the most a monitor's label can say inside a rectangle `width` cells across, inside its border
+// - name and resolution, then just the name, then as much of the name as fits
+pub fn monitor_label(monitor: &Monitor, width: usize) -> String {
+    let name = format!("{}{}", monitor.display_name(), if monitor.is_primary { "*" } else { "" });
+    let full = format!("{} ({}x{})", name, monitor.displayed_resolution.0, monitor.displayed_resolution.1);
+    let (open, close) = if monitor.is_selected { ("<", ">") } else { ("", "") };
+    let room = width.saturating_sub(2 + open.len() + close.len());
+
+    let label = if full.chars().count() <= room { full } else { fit_label(&name, room) };
+    if label.is_empty() {
+        return label;
+    }
+    format!("{}{}{}", open, label, close)
+}
+
+// draw monitors as defined
+fn draw_monitors(f: &mut ratatui::Frame, area: Rect, monitors: &[Monitor], app: &App) {
+    let transform = CanvasTransform::new(area, monitors, app);
+    let (canvas_width, canvas_height) = transform.bounds();
 
     let monitor_data: Vec<_> = monitors.iter().enumerate().map(|(i, m)| {
-        (i, m.position, m.displayed_resolution, m.is_selected, m.is_primary, m.is_enabled, m.name.clone())
+        let (width, _) = transform.to_canvas_size(m.displayed_resolution);
+        (i, m.position, m.displayed_resolution, m.is_selected, m.is_primary, m.is_enabled, monitor_label(m, width.floor() as usize))
     }).collect();
 
     let canvas = Canvas::default()
-        .x_bounds([0.0, total_width])
-        .y_bounds([0.0, total_height])
+        .x_bounds([0.0, canvas_width])
+        .y_bounds([0.0, canvas_height])
         .paint(move |ctx| {
-            for (i, position, displayed_resolution, is_selected, is_primary, is_enabled, mut name) in monitor_data.iter().cloned() {
+            for (i, position, displayed_resolution, is_selected, is_primary, is_enabled, label) in monitor_data.iter().cloned() {
                 if !is_enabled { continue }
-                let x = position.0 as f64 * scale_x + total_width * (1.0 - scale_x)/2.0;
-                let y = total_height - (position.1 as f64 * scale_y + total_height * (1.0 - scale_y)/2.0);
-                let width = displayed_resolution.0 as f64 * scale_x;
-                let height = displayed_resolution.1 as f64 * scale_y * -1.0;
+                let (x, y) = transform.to_canvas(position);
+                let (width, height) = transform.to_canvas_size(displayed_resolution);
+                let height = -height;
 
                 let color = if is_selected {
                     Color::LightMagenta
@@ -935,23 +2031,12 @@ fn draw_monitors(f: &mut ratatui::Frame, area: Rect, monitors: &[Monitor], app:
                     color,
                 });
 
-                // Draw monitor name
-                if is_selected {
-                    if is_primary {
-                        name = format!("<{}* ({}x{})>", name, displayed_resolution.0, displayed_resolution.1);
-                    } else {
-                        name = format!("<{} ({}x{})>", name, displayed_resolution.0, displayed_resolution.1);
-                    }
-                } else if is_primary {
-                    name = format!("{}* ({}x{})", name, displayed_resolution.0, displayed_resolution.1);
-                } else {
-                    name = format!("{} ({}x{})", name, displayed_resolution.0, displayed_resolution.1)
-                }
+                // canvas x is in cells, so the label centres exactly
                 ctx.print(
-                    x + width/2.0 - (name.chars().count()*2) as f64,
+                    x + (width - label.chars().count() as f64) / 2.0,
                     y + height/2.0,
                     Span::styled(
-                        name,
+                        label,
                         Style::default().fg(Color::Black).bg(color)
                     ),
                 );
@@ -960,4 +2045,3 @@ fn draw_monitors(f: &mut ratatui::Frame, area: Rect, monitors: &[Monitor], app:
 
     f.render_widget(canvas, area);
 }
-
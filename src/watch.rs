@@ -0,0 +1,105 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::Duration;
+
+use crate::diff::*;
+use crate::plan::*;
+use crate::profile::*;
+use crate::xrandr::*;
+
+pub const DRM_ROOT: &str = "/sys/class/drm";
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum WatchEvent {
+    Applied(String),        // name of the profile applied
+    NoMatch(Vec<String>),   // fingerprint of the monitors, nothing saved for it
+}
+
+// connection status of every drm connector, e.g. card0-HDMI-A-1 -> connected
+pub fn read_connector_status(drm_root: &Path) -> BTreeMap<String, bool> {
+    let mut connectors: BTreeMap<String, bool> = BTreeMap::new();
+    let Ok(entries) = fs::read_dir(drm_root) else { return connectors };
+
+    for entry in entries.flatten() {
+        let name = entry.file_name().to_string_lossy().to_string();
+        if !name.starts_with("card") || !name.contains('-') {
+            continue;
+        }
+        if let Ok(status) = fs::read_to_string(entry.path().join("status")) {
+            connectors.insert(name, status.trim() == "connected");
+        }
+    }
+
+    connectors
+}
+
+// watches for outputs being connected or disconnected and applies the matching profile
+pub struct Watcher<B: Backend> {
+    pub backend: B,
+    pub drm_root: PathBuf,
+    pub profiles_dir: PathBuf,
+    connectors: Option<BTreeMap<String, bool>>,
+}
+
+impl<B: Backend> Watcher<B> {
+    pub fn new(backend: B, drm_root: PathBuf, profiles_dir: PathBuf) -> Watcher<B> {
+        Watcher { backend, drm_root, profiles_dir, connectors: None }
+    }
+
+    // check for hotplug events, the first poll always counts as a change
+    // - the change is only taken as handled once it's gone through, so a failed apply is retried
+    //   on the next poll
+    pub fn poll(&mut self) -> io::Result<Option<WatchEvent>> {
+        let connectors = read_connector_status(&self.drm_root);
+        if self.connectors.as_ref() == Some(&connectors) {
+            return Ok(None);
+        }
+        let event = self.apply_matching_profile()?;
+        self.connectors = Some(connectors);
+        Ok(Some(event))
+    }
+
+    pub fn apply_matching_profile(&mut self) -> io::Result<WatchEvent> {
+        let before = self.backend.query()?;
+        let mut monitors = before.clone();
+        match find_matching_profile(&self.profiles_dir, &monitors)? {
+            Some(profile) => {
+                profile.apply_to(&mut monitors)?;
+                if diff_args(&before, &monitors).is_empty() {
+                    return Ok(WatchEvent::Applied(profile.name));
+                }
+                let result = apply_planned(&mut self.backend, &before, &monitors);
+                if !result.success {
+                    return Err(io::Error::other(format!(
+                        "xrandr failed to apply profile {} ({}): {}",
+                        profile.name,
+                        result.status,
+                        result.stderr.trim()
+                    )));
+                }
+                Ok(WatchEvent::Applied(profile.name))
+            }
+            None => Ok(WatchEvent::NoMatch(fingerprint(&monitors))),
+        }
+    }
+}
+
+// run until killed, reporting what happens on each change
+pub fn run_watch<B: Backend>(backend: B) -> io::Result<()> {
+    let mut watcher = Watcher::new(backend, PathBuf::from(DRM_ROOT), profiles_dir());
+    println!("Watching {} for monitor changes, profiles in {}", DRM_ROOT, watcher.profiles_dir.display());
+
+    loop {
+        match watcher.poll() {
+            Ok(Some(WatchEvent::Applied(name))) => println!("Applied profile {}", name),
+            Ok(Some(WatchEvent::NoMatch(identities))) => println!("No profile saved for {}", identities.join(", ")),
+            Ok(None) => {}
+            Err(err) => eprintln!("Error: {}", err),
+        }
+        thread::sleep(POLL_INTERVAL);
+    }
+}
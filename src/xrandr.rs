@@ -1,29 +1,194 @@
-use std::borrow::Cow;
 use std::collections::HashMap;
-use std::process::{Command, Output};
+use std::fs;
+use std::os::unix::process::ExitStatusExt;
+use std::path::{Path, PathBuf};
+use std::process::{Command, ExitStatus, Output};
 use std::io;
-use std::str::Lines;
 
+use crate::edid::*;
 use crate::monitor::*;
 use crate::debug::xrandr_debug::*;
 
+// where monitor information comes from and layouts are sent to
+// - layouts are sent from a thread of their own, so backends have to be Send
+pub trait Backend: Send {
+    fn query(&mut self) -> io::Result<Monitors>;
+    fn apply(&mut self, args: &[String]) -> io::Result<Output>;
+}
+
+// talks to the real xrandr
+// - layouts are sent to program, which is xrandr unless something else is put in its place
+pub struct XrandrBackend {
+    pub program: PathBuf,
+}
+
+impl Default for XrandrBackend {
+    fn default() -> XrandrBackend {
+        XrandrBackend { program: PathBuf::from("xrandr") }
+    }
+}
+
+impl Backend for XrandrBackend {
+    fn query(&mut self) -> io::Result<Monitors> {
+        let mut monitors = get_monitor_info(false)?;
+        monitor_proximity(&mut monitors);
+        Ok(monitors)
+    }
+
+    fn apply(&mut self, args: &[String]) -> io::Result<Output> {
+        Command::new(&self.program).args(args).output()
+    }
+}
+
+// mock monitors for debug mode, applying does nothing
+pub struct DebugBackend;
+
+impl Backend for DebugBackend {
+    fn query(&mut self) -> io::Result<Monitors> {
+        let mut monitors = get_monitor_info(true)?;
+        monitor_proximity(&mut monitors);
+        Ok(monitors)
+    }
+
+    fn apply(&mut self, _args: &[String]) -> io::Result<Output> {
+        Ok(Output { status: ExitStatus::from_raw(0), stdout: Vec::new(), stderr: Vec::new() })
+    }
+}
+
+// monitors loaded from a saved `xrandr --query`, applying goes to the real xrandr
+pub struct FileBackend {
+    pub path: PathBuf,
+}
+
+impl Backend for FileBackend {
+    fn query(&mut self) -> io::Result<Monitors> {
+        let mut monitors = read_monitor_info(&self.path)?;
+        monitor_proximity(&mut monitors);
+        Ok(monitors)
+    }
+
+    fn apply(&mut self, args: &[String]) -> io::Result<Output> {
+        XrandrBackend::default().apply(args)
+    }
+}
+
+// queries another backend, but only reports what would have been applied
+pub struct DryRunBackend(pub Box<dyn Backend>);
+
+impl Backend for DryRunBackend {
+    fn query(&mut self) -> io::Result<Monitors> {
+        self.0.query()
+    }
+
+    fn apply(&mut self, args: &[String]) -> io::Result<Output> {
+        let stdout = format!("dry run, not applied: xrandr {}\n", args.join(" "));
+        Ok(Output { status: ExitStatus::from_raw(0), stdout: stdout.into_bytes(), stderr: Vec::new() })
+    }
+}
+
+impl Backend for Box<dyn Backend> {
+    fn query(&mut self) -> io::Result<Monitors> {
+        (**self).query()
+    }
+
+    fn apply(&mut self, args: &[String]) -> io::Result<Output> {
+        (**self).apply(args)
+    }
+}
+
+// pick a backend from the command line options
+pub fn make_backend(debug: bool, input: Option<&Path>, dry_run: bool) -> Box<dyn Backend> {
+    let backend: Box<dyn Backend> = match input {
+        Some(path) => Box::new(FileBackend { path: path.to_path_buf() }),
+        None if debug => Box::new(DebugBackend),
+        None => Box::new(XrandrBackend::default()),
+    };
+    if dry_run { Box::new(DryRunBackend(backend)) } else { backend }
+}
+
+// what happened when a layout was sent to the backend
+#[derive(Debug, Clone, PartialEq)]
+pub struct ApplyResult {
+    pub command: String,
+    pub success: bool,
+    pub status: String,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+// send arguments to the backend, capturing everything instead of panicking
+pub fn run_apply(backend: &mut dyn Backend, args: &[String]) -> ApplyResult {
+    let command = format!("xrandr {}", args.join(" "));
+    match backend.apply(args) {
+        Ok(output) => ApplyResult {
+            command,
+            success: output.status.success(),
+            status: output.status.to_string(),
+            stdout: String::from_utf8_lossy(&output.stdout).to_string(),
+            stderr: String::from_utf8_lossy(&output.stderr).to_string(),
+        },
+        Err(err) => ApplyResult {
+            command,
+            success: false,
+            status: "failed to run xrandr".to_string(),
+            stdout: String::new(),
+            stderr: err.to_string(),
+        },
+    }
+}
+
 // get initial monitor information from xrandr
 pub fn get_monitor_info(debug: bool) -> io::Result<Monitors> {
-    let output: Output;
-    let xrandr_lines : Lines;
-    let stdout: Cow<str>;
+    let mut monitors = parse_monitor_info(&query_output(debug, None)?);
+    if debug {
+        attach_edids(&mut monitors, &parse_verbose_edids(XRANDR_VERBOSE_EDID));
+        return Ok(monitors);
+    }
 
+    let names: Vec<String> = monitors.iter().map(|m| m.name.clone()).collect();
+    attach_edids(&mut monitors, &query_edids(&names));
+    Ok(monitors)
+}
+
+// the raw `xrandr --query` text monitors are parsed from, wherever it comes from
+pub fn query_output(debug: bool, input: Option<&Path>) -> io::Result<String> {
+    if let Some(path) = input {
+        return fs::read_to_string(path)
+            .map_err(|err| io::Error::new(err.kind(), format!("can't read {}: {}", path.display(), err)));
+    }
     if debug {
-        xrandr_lines = XRANDR_OUTPUT.lines();
-    } else {
-        // TODO: Handle xrandr not found
-        output = Command::new("xrandr")
-            .arg("--query")
-            .output()?;
-        stdout = String::from_utf8_lossy(&output.stdout);
-        xrandr_lines = stdout.lines();
+        return Ok(XRANDR_OUTPUT.to_string());
     }
 
+    // TODO: Handle xrandr not found
+    let output = Command::new("xrandr")
+        .arg("--query")
+        .output()?;
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+// load monitors from saved `xrandr --query` output, e.g. sent along with a bug report
+pub fn read_monitor_info(path: &Path) -> io::Result<Monitors> {
+    let text = query_output(false, Some(path))?;
+    let mut monitors = parse_monitor_info(&text);
+    if monitors.is_empty() {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, format!("no connected outputs found in {}", path.display())));
+    }
+    // only there if it was saved from `xrandr --verbose`
+    attach_edids(&mut monitors, &parse_verbose_edids(&text));
+    Ok(monitors)
+}
+
+// attach panel identities so monitors can be recognised whichever port they're on
+fn attach_edids(monitors: &mut Monitors, edids: &HashMap<String, Edid>) {
+    for monitor in monitors.iter_mut() {
+        monitor.edid = edids.get(&monitor.name).cloned();
+    }
+}
+
+// parse the output of `xrandr --query`
+pub fn parse_monitor_info(xrandr_output: &str) -> Monitors {
+    let xrandr_lines = xrandr_output.lines();
     let mut selected_framerate = 0.0;
     let mut selected_resolution = (0, 0);
     let mut monitors: Monitors = Vec::new();
@@ -49,20 +214,23 @@ pub fn get_monitor_info(debug: bool) -> io::Result<Monitors> {
                     left: monitor.left,
                     right: monitor.right,
                     up: monitor.up,
-                    down: monitor.down
+                    down: monitor.down,
+                    edid: monitor.edid
                 });
             }
 
             // Reset for the next monitor
             current_resolutions = HashMap::new();
 
-            let is_enabled = line.ends_with("mm");
-
             // Parse monitor name and primary status
             let parts: Vec<&str> = line.split_whitespace().collect();
             let name = parts[0].to_string();
             let is_primary = parts.contains(&"primary");
-            let res_pos_part: Vec<&str> = if is_primary { parts[3].split("+").collect() } else if !is_enabled { "0x0+0+0".split("+").collect() } else { parts[2].split("+").collect() };
+
+            // geometry looks like 2560x1440+0+0, a monitor that's off has none, even if it's primary
+            let geometry = parts.iter().find(|part| part.contains('x') && part.split('+').count() == 3);
+            let is_enabled = line.ends_with("mm") && geometry.is_some();
+            let res_pos_part: Vec<&str> = geometry.filter(|_| is_enabled).unwrap_or(&"0x0+0+0").split('+').collect();
             let resolution_part = res_pos_part[0];
             let position_part = res_pos_part[1];
 
@@ -86,11 +254,12 @@ pub fn get_monitor_info(debug: bool) -> io::Result<Monitors> {
                 is_primary,
                 available_resolutions: HashMap::new(),
                 is_selected: false,
-                is_enabled: is_enabled,
+                is_enabled,
                 left: None,
                 right: None,
                 up: None,
-                down: None
+                down: None,
+                edid: None
             });
         } else if line.contains(" disconnected") {
             // Push the previous monitor to the list if there was one
@@ -109,7 +278,8 @@ pub fn get_monitor_info(debug: bool) -> io::Result<Monitors> {
                     left: monitor.left,
                     right: monitor.right,
                     up: monitor.up,
-                    down: monitor.down
+                    down: monitor.down,
+                    edid: monitor.edid
                 });
             }
 
@@ -117,12 +287,12 @@ pub fn get_monitor_info(debug: bool) -> io::Result<Monitors> {
             current_resolutions = HashMap::new();
 
             current_monitor = None;
-        } else if let Some(_) = current_monitor.as_mut() {
+        } else if current_monitor.is_some() {
             // Parse the resolution and framerates
-            let parts: Vec<&str> = line.trim().split_whitespace().collect();
+            let parts: Vec<&str> = line.split_whitespace().collect();
 
             // Check if the first part is in the format of a resolution (e.g., "2560x1600")
-            if let Some(res_part) = parts.get(0) {
+            if let Some(res_part) = parts.first() {
                 if res_part.contains('x') {
                     let res: Vec<i32> = res_part
                         .split('x')
@@ -150,7 +320,7 @@ pub fn get_monitor_info(debug: bool) -> io::Result<Monitors> {
 
                         // Insert the resolution and framerates into the hashmap
                         current_resolutions.entry(resolution)
-                            .or_insert_with(Vec::new)
+                            .or_default()
                             .extend(framerates);
                     }
                 }
@@ -164,7 +334,7 @@ pub fn get_monitor_info(debug: bool) -> io::Result<Monitors> {
             name: monitor.name,
             resolution: selected_resolution,
             displayed_resolution: monitor.displayed_resolution,
-            scale: monitor.displayed_resolution.0 as f32/selected_resolution.0 as f32,
+            scale: selected_resolution.0 as f32/monitor.displayed_resolution.0 as f32,
             position: monitor.position,
             framerate: selected_framerate,
             is_primary: monitor.is_primary,
@@ -174,11 +344,83 @@ pub fn get_monitor_info(debug: bool) -> io::Result<Monitors> {
             left: monitor.left,
             right: monitor.right,
             up: monitor.up,
-            down: monitor.down
+            down: monitor.down,
+            edid: monitor.edid
         });
     }
 
     // setup proximity sensor. TODO: allow for margin of error
-    Ok(monitors)
+    monitors
+}
+
+// what xrandr says about an output beyond its layout, for the connections popup
+#[derive(Debug, Clone, PartialEq)]
+pub struct OutputInfo {
+    pub name: String,
+    pub connected: bool,
+    pub physical_size: Option<(i32, i32)>,  // in mm, only reported while the output is on
+    pub preferred: Option<(i32, i32)>,      // the mode marked with + by xrandr
+}
+
+// every output in `xrandr --query`, including the ones with nothing plugged in
+pub fn parse_outputs(xrandr_output: &str) -> Vec<OutputInfo> {
+    let mut outputs: Vec<OutputInfo> = Vec::new();
+    for line in xrandr_output.lines() {
+        let parts: Vec<&str> = line.split_whitespace().collect();
+        if matches!(parts.get(1), Some(&"connected") | Some(&"disconnected")) {
+            // e.g. ... 597mm x 336mm
+            let physical_size = match parts[parts.len().saturating_sub(3)..] {
+                [width, "x", height] => width.strip_suffix("mm").zip(height.strip_suffix("mm"))
+                    .and_then(|(w, h)| Some((w.parse().ok()?, h.parse().ok()?)))
+                    .filter(|&(w, h)| w > 0 && h > 0),
+                _ => None,
+            };
+            outputs.push(OutputInfo { name: parts[0].to_string(), connected: parts[1] == "connected", physical_size, preferred: None });
+        } else if let Some(output) = outputs.last_mut() {
+            let is_preferred = parts.iter().skip(1).any(|rate| rate.contains('+'));
+            if output.preferred.is_none() && is_preferred {
+                output.preferred = parts.first()
+                    .and_then(|mode| mode.split_once('x'))
+                    .and_then(|(w, h)| Some((w.parse().ok()?, h.parse().ok()?)));
+            }
+        }
+    }
+    outputs
 }
 
+
+// build the xrandr arguments for a single enabled output
+pub fn output_args(monitor: &Monitor) -> Vec<String> {
+    let mut args: Vec<String> = vec!["--output".to_string(), monitor.name.to_string()];
+    if monitor.is_primary { args.push("--primary".to_string()); }
+    args.push("--mode".to_string());
+    args.push(format!("{}x{}", monitor.resolution.0, monitor.resolution.1));
+    args.push("--rate".to_string());
+    args.push(monitor.framerate.to_string());
+    args.push("--pos".to_string());
+    args.push(format!("{}x{}", monitor.position.0, monitor.position.1));
+    args.push("--scale".to_string());
+    args.push(format!("{:.2}", 1.0/monitor.scale));
+    args
+}
+
+// the xrandr arguments turning an output off
+pub fn off_args(monitor: &Monitor) -> Vec<String> {
+    vec!["--output".to_string(), monitor.name.to_string(), "--off".to_string()]
+}
+
+// arguments for the whole layout, turning off anything disabled
+pub fn convert_monitors_to_args(monitors: &Monitors, debug: bool) -> Vec<String> {
+    let mut args: Vec<String> = Vec::new();
+    for element in monitors.iter() {
+        if !element.is_enabled { continue; }
+        if debug { args.push("\n> ".to_string()); }
+        args.extend(output_args(element));
+    }
+    for element in monitors.iter().filter(|m| !m.is_enabled) {
+        if debug { args.push("\n> ".to_string()); }
+        args.extend(off_args(element));
+    }
+
+    args
+}